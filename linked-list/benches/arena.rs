@@ -0,0 +1,78 @@
+//! `ArenaList` against `UnsafeLinkedList`: building, scanning, mutating and cursor splits.
+//!
+//! run: cargo bench --bench arena
+
+mod common;
+
+use common::bench;
+use linked_list::arena_list::ArenaList;
+use linked_list::unsafe_linked_list::UnsafeLinkedList;
+
+const LEN: u64 = 1_000_000;
+
+fn main() {
+    bench("UnsafeLinkedList push_back", LEN, || {
+        (0..LEN).collect::<UnsafeLinkedList<_>>().len() as u64
+    });
+    bench("ArenaList push_back", LEN, || {
+        (0..LEN).collect::<ArenaList<_>>().len() as u64
+    });
+
+    let mut linked: UnsafeLinkedList<u64> = (0..LEN).collect();
+    let mut arena: ArenaList<u64> = (0..LEN).collect();
+
+    bench("UnsafeLinkedList iter", LEN, || linked.iter().sum());
+    bench("ArenaList iter", LEN, || arena.iter().sum());
+
+    bench("UnsafeLinkedList iter_mut", LEN, || {
+        linked.iter_mut().map(|x| *x += 1).count() as u64
+    });
+    bench("ArenaList iter_mut", LEN, || {
+        arena.iter_mut().map(|x| *x += 1).count() as u64
+    });
+
+    // Half the slots vacant: both walks only visit the live ones
+    let mut sparse: ArenaList<u64> = (0..2 * LEN).collect();
+    let mut cursor = sparse.cursor_mut();
+    cursor.move_next();
+    while cursor.remove_current().is_some() {
+        cursor.move_next();
+    }
+    bench("ArenaList iter, half vacant", LEN, || sparse.iter().sum());
+    bench("ArenaList iter_mut, half vacant", LEN, || {
+        sparse.iter_mut().map(|x| *x += 1).count() as u64
+    });
+
+    // Pushing at alternate ends leaves list order and slot order unrelated, the worst case for
+    // iter_mut's sort
+    let mut shuffled = ArenaList::new();
+    for i in 0..LEN {
+        if i % 2 == 0 {
+            shuffled.push(i);
+        } else {
+            shuffled.push_back(i);
+        }
+    }
+    bench("ArenaList iter, shuffled", LEN, || shuffled.iter().sum());
+    bench("ArenaList iter_mut, shuffled", LEN, || {
+        shuffled.iter_mut().map(|x| *x += 1).count() as u64
+    });
+
+    // Both seek to the middle, but only the arena has to move the split-off half
+    bench("UnsafeLinkedList split + splice", LEN, || {
+        let mut cursor = linked.cursor_mut();
+        cursor.seek(LEN as usize / 2);
+        let back = cursor.split_after();
+        let len = back.len() as u64;
+        cursor.splice_after(back);
+        len
+    });
+    bench("ArenaList split + splice", LEN, || {
+        let mut cursor = arena.cursor_mut();
+        cursor.seek(LEN as usize / 2);
+        let back = cursor.split_after();
+        let len = back.len() as u64;
+        cursor.splice_after(back);
+        len
+    });
+}
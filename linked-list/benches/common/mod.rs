@@ -0,0 +1,22 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ROUNDS: u32 = 20;
+
+/// Prints the best of `ROUNDS` timed runs of `run`, also per element of a `len` long list.
+pub fn bench(name: &str, len: u64, mut run: impl FnMut() -> u64) {
+    // Warm up caches and the allocator before timing
+    black_box(run());
+
+    let mut best = Duration::MAX;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        black_box(run());
+        best = best.min(start.elapsed());
+    }
+    println!(
+        "{name:<40} {:>10.2?}  ({:.2} ns/elem)",
+        best,
+        best.as_nanos() as f64 / len as f64
+    );
+}
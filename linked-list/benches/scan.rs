@@ -0,0 +1,31 @@
+//! Sequential scan: `UnsafeLinkedList` against `UnrolledList`.
+//!
+//! run: cargo bench --bench scan
+
+mod common;
+
+use common::bench;
+use linked_list::unrolled_list::UnrolledList;
+use linked_list::unsafe_linked_list::UnsafeLinkedList;
+
+const LEN: u64 = 1_000_000;
+
+fn main() {
+    let linked: UnsafeLinkedList<u64> = (0..LEN).collect();
+    let unrolled_16: UnrolledList<u64, 16> = (0..LEN).collect();
+    let unrolled_64: UnrolledList<u64, 64> = (0..LEN).collect();
+
+    bench("UnsafeLinkedList iter", LEN, || linked.iter().sum());
+    bench("UnrolledList<_, 16> iter", LEN, || unrolled_16.iter().sum());
+    bench("UnrolledList<_, 64> iter", LEN, || unrolled_64.iter().sum());
+
+    bench("UnsafeLinkedList iter().rev()", LEN, || {
+        linked.iter().rev().sum()
+    });
+    bench("UnrolledList<_, 16> iter().rev()", LEN, || {
+        unrolled_16.iter().rev().sum()
+    });
+    bench("UnrolledList<_, 64> iter().rev()", LEN, || {
+        unrolled_64.iter().rev().sum()
+    });
+}
@@ -0,0 +1,247 @@
+use std::alloc::{self, Layout};
+use std::ptr::NonNull;
+
+/// Source of node memory for `UnsafeLinkedList` and `UnsafeQueue`.
+///
+/// # Safety
+///
+/// `allocate` must return memory valid for `layout` that stays valid until it is passed back to
+/// `deallocate` with the same layout, even if the value that allocated it has been dropped by
+/// then: moving nodes between lists can leave them in a list that owns a different value. An
+/// arena that releases everything when dropped must therefore be shared as `&Arena`, whose
+/// borrow keeps every list using it from outliving the arena, and may then ignore `deallocate`.
+///
+/// Values of the same allocator type must be interchangeable for deallocation: `deallocate` on
+/// any value may be handed a block that another value of the type allocated, and must either free
+/// it correctly or ignore it. Splicing, `append`, `prepend_list` and the `split_*` methods rely
+/// on this, since they move nodes between lists and clone the allocator for the new list.
+pub unsafe trait NodeAllocator {
+    fn allocate(&self, layout: Layout) -> NonNull<u8>;
+
+    /// # Safety
+    ///
+    /// `ptr` must come from `allocate`, on this value or another value of the same type, with
+    /// the same `layout`, and must not have been deallocated since.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Global;
+
+unsafe impl NodeAllocator for Global {
+    fn allocate(&self, layout: Layout) -> NonNull<u8> {
+        let ptr = unsafe { alloc::alloc(layout) };
+        NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        alloc::dealloc(ptr.as_ptr(), layout)
+    }
+}
+
+unsafe impl<A: NodeAllocator + ?Sized> NodeAllocator for &A {
+    fn allocate(&self, layout: Layout) -> NonNull<u8> {
+        (**self).allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).deallocate(ptr, layout)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Global, NodeAllocator};
+    use crate::unsafe_linked_list::UnsafeLinkedList;
+    use crate::unsafe_queue::UnsafeQueue;
+    use std::alloc::Layout;
+    use std::cell::Cell;
+    use std::ptr::NonNull;
+    use std::rc::Rc;
+
+    #[derive(Default)]
+    struct Counting {
+        live: Cell<usize>,
+        total: Cell<usize>,
+    }
+
+    unsafe impl NodeAllocator for Counting {
+        fn allocate(&self, layout: Layout) -> NonNull<u8> {
+            self.live.set(self.live.get() + 1);
+            self.total.set(self.total.get() + 1);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.live.set(self.live.get() - 1);
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    struct Bump {
+        chunk: NonNull<u8>,
+        layout: Layout,
+        used: Cell<usize>,
+    }
+
+    impl Bump {
+        fn new(size: usize) -> Self {
+            let layout = Layout::from_size_align(size, 64).unwrap();
+            Bump {
+                chunk: Global.allocate(layout),
+                layout,
+                used: Cell::new(0),
+            }
+        }
+    }
+
+    unsafe impl NodeAllocator for Bump {
+        fn allocate(&self, layout: Layout) -> NonNull<u8> {
+            let start = self.used.get().next_multiple_of(layout.align());
+            let end = start + layout.size();
+            assert!(end <= self.layout.size(), "bump arena exhausted");
+            self.used.set(end);
+            unsafe { NonNull::new_unchecked(self.chunk.as_ptr().add(start)) }
+        }
+
+        unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+    }
+
+    // Owned by its list rather than borrowed. Blocks come from `Global`, so they stay valid after
+    // the value that allocated them is dropped.
+    #[derive(Clone, Default)]
+    struct Owned {
+        live: Rc<Cell<isize>>,
+    }
+
+    unsafe impl NodeAllocator for Owned {
+        fn allocate(&self, layout: Layout) -> NonNull<u8> {
+            self.live.set(self.live.get() + 1);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.live.set(self.live.get() - 1);
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    impl Drop for Bump {
+        fn drop(&mut self) {
+            unsafe { Global.deallocate(self.chunk, self.layout) }
+        }
+    }
+
+    #[test]
+    fn list_returns_every_node() {
+        let counting = Counting::default();
+        {
+            let mut list = UnsafeLinkedList::new_in(&counting);
+            list.push_back(2);
+            list.push(1);
+            list.push_back(3);
+            assert_eq!(counting.live.get(), 3);
+            assert_eq!(list.pop_first(), Some(1));
+            assert_eq!(counting.live.get(), 2);
+
+            let mut cursor = list.cursor_mut();
+            cursor.move_next();
+            cursor.insert_before(10);
+            cursor.insert_after(20);
+            assert_eq!(cursor.remove_current(), Some(2));
+            let back = cursor.split_after();
+            assert_eq!(back.iter().collect::<Vec<_>>(), [&3]);
+            assert!(std::ptr::eq(*back.allocator(), &counting));
+
+            let copy = list.clone();
+            assert_eq!(copy.iter().collect::<Vec<_>>(), [&10, &20]);
+            assert_eq!(counting.live.get(), 5);
+        }
+        assert_eq!(counting.live.get(), 0);
+        assert_eq!(counting.total.get(), 7);
+    }
+
+    #[test]
+    fn queue_returns_every_node() {
+        let counting = Counting::default();
+        {
+            let mut queue = UnsafeQueue::new_in(&counting);
+            queue.push(1);
+            queue.push(2);
+            assert_eq!(queue.pop(), Some(1));
+            queue.push(3);
+            assert_eq!(counting.live.get(), 2);
+
+            let mut rest = queue.into_iter();
+            assert_eq!(rest.next(), Some(2));
+        }
+        assert_eq!(counting.live.get(), 0);
+        assert_eq!(counting.total.get(), 3);
+    }
+
+    #[test]
+    fn bump_arena() {
+        let arena = Bump::new(64 * 1024);
+        let marker = Rc::new(());
+        {
+            let mut list = UnsafeLinkedList::new_in(&arena);
+            let mut queue = UnsafeQueue::new_in(&arena);
+            for i in 0..1000 {
+                list.push_back((i, Rc::clone(&marker)));
+                queue.push(i);
+            }
+            assert_eq!(list.iter().map(|(i, _)| i).sum::<i32>(), 499_500);
+            assert_eq!(queue.iter().sum::<i32>(), 499_500);
+
+            // Freed slots are not reused, the arena only grows
+            let used = arena.used.get();
+            list.pop_first();
+            list.push((0, Rc::clone(&marker)));
+            assert!(arena.used.get() > used);
+        }
+        // The arena keeps the memory, but values were still dropped
+        assert_eq!(Rc::strong_count(&marker), 1);
+    }
+
+    #[test]
+    fn spliced_nodes_outlive_their_owned_allocator() {
+        let (a, b) = (Owned::default(), Owned::default());
+        let (a_live, b_live) = (Rc::clone(&a.live), Rc::clone(&b.live));
+        let mut list = UnsafeLinkedList::new_in(a);
+        list.extend([1, 4]);
+        {
+            let mut middle = UnsafeLinkedList::new_in(b);
+            middle.extend([2, 3]);
+            let mut cursor = list.cursor_mut();
+            cursor.move_next();
+            cursor.splice_after(middle);
+        }
+        // `b` is gone, its nodes now belong to a list that frees them through `a`
+        assert_eq!(b_live.get(), 2);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+        assert_eq!(list.back(), Some(&4));
+        drop(list);
+        assert_eq!(a_live.get() + b_live.get(), 0);
+    }
+
+    #[test]
+    fn appended_nodes_outlive_their_owned_allocator() {
+        let (a, b, c) = (Owned::default(), Owned::default(), Owned::default());
+        let live = [&a.live, &b.live, &c.live].map(Rc::clone);
+        let mut list = UnsafeLinkedList::new_in(a);
+        list.push(2);
+        let mut back = UnsafeLinkedList::new_in(b);
+        back.push(3);
+        let mut front = UnsafeLinkedList::new_in(c);
+        front.push(1);
+
+        list.append(&mut back);
+        list.prepend_list(&mut front);
+        drop(back);
+        drop(front);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(list.pop_back(), Some(3));
+        drop(list);
+        assert_eq!(live.iter().map(|live| live.get()).sum::<isize>(), 0);
+    }
+}
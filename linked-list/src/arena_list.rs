@@ -0,0 +1,1075 @@
+#![forbid(unsafe_code)]
+
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::mem;
+
+pub struct ArenaList<T> {
+    slots: Vec<Slot<T>>,
+    first: Link,
+    last: Link,
+    free: Link,
+    len: usize,
+}
+
+pub struct Iter<'a, T> {
+    slots: &'a [Slot<T>],
+    first: Link,
+    last: Link,
+    len: usize,
+}
+
+pub struct IterMut<'a, T> {
+    values: std::vec::IntoIter<&'a mut T>,
+}
+
+pub struct IntoIter<T> {
+    list: ArenaList<T>,
+}
+
+pub struct Cursor<'a, T> {
+    cur: Link,
+    list: &'a ArenaList<T>,
+    index: Option<usize>,
+}
+
+pub struct CursorMut<'a, T> {
+    cur: Link,
+    list: &'a mut ArenaList<T>,
+    index: Option<usize>,
+}
+
+type Link = Option<u32>;
+
+enum Slot<T> {
+    Occupied { value: T, prev: Link, next: Link },
+    Vacant { next_free: Link },
+}
+
+impl<T> ArenaList<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        ArenaList {
+            slots: Vec::with_capacity(capacity),
+            first: None,
+            last: None,
+            free: None,
+            len: 0,
+        }
+    }
+
+    pub fn cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self,
+            cur: None,
+            index: None,
+        }
+    }
+
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            list: self,
+            cur: None,
+            index: None,
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        let new = self.alloc(value, None, self.first);
+        if let Some(old) = self.first {
+            *self.prev_mut(old) = Some(new);
+        } else {
+            self.last = Some(new);
+        }
+        self.first = Some(new);
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        let new = self.alloc(value, self.last, None);
+        if let Some(old) = self.last {
+            *self.next_mut(old) = Some(new);
+        } else {
+            self.first = Some(new);
+        }
+        self.last = Some(new);
+    }
+
+    pub fn pop_first(&mut self) -> Option<T> {
+        self.first.map(|node| self.unlink(node))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.last.map(|node| self.unlink(node))
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.first = None;
+        self.last = None;
+        self.free = None;
+        self.len = 0;
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.first.map(|node| self.value(node))
+    }
+
+    pub fn first_mut(&mut self) -> Option<&mut T> {
+        self.first.map(|node| self.value_mut(node))
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.last.map(|node| self.value(node))
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.last.map(|node| self.value_mut(node))
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            slots: &self.slots,
+            first: self.first,
+            last: self.last,
+            len: self.len,
+        }
+    }
+
+    /// Without unsafe the slab can only be handed out as `&mut T`s by splitting it, so this
+    /// collects them in list order up front, one allocation. While most slots are in use they
+    /// are split one by one, O(slots) = O(len). A sparse slab sorts the slots of the live nodes
+    /// instead and splits only those, O(len log len), so vacant slots never dominate.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let values = if self.slots.len() <= 2 * self.len {
+            self.values_by_slot()
+        } else {
+            self.values_by_sorted_slot()
+        };
+        IterMut {
+            values: values.into_iter(),
+        }
+    }
+
+    fn values_by_slot(&mut self) -> Vec<&mut T> {
+        let mut slots: Vec<_> = self
+            .slots
+            .iter_mut()
+            .map(|slot| match slot {
+                Slot::Occupied { value, next, .. } => Some((value, *next)),
+                Slot::Vacant { .. } => None,
+            })
+            .collect();
+        let mut values = Vec::with_capacity(self.len);
+        let mut cur = self.first;
+        while let Some(node) = cur {
+            let (value, next) = slots[node as usize].take().expect("node visited twice");
+            values.push(value);
+            cur = next;
+        }
+        values
+    }
+
+    fn values_by_sorted_slot(&mut self) -> Vec<&mut T> {
+        // Slot in the high half, position in the list in the low half
+        let mut order = Vec::with_capacity(self.len);
+        let mut cur = self.first;
+        while let Some(node) = cur {
+            order.push((node as u64) << 32 | order.len() as u64);
+            cur = self.links(node).1;
+        }
+        order.sort_unstable();
+
+        let mut values: Vec<Option<&mut T>> = (0..order.len()).map(|_| None).collect();
+        let (mut rest, mut start) = (&mut self.slots[..], 0);
+        for entry in order {
+            let (node, position) = ((entry >> 32) as usize, entry as u32 as usize);
+            let (_, from) = mem::take(&mut rest).split_at_mut(node - start);
+            let (slot, after) = from.split_first_mut().unwrap();
+            (rest, start) = (after, node + 1);
+            values[position] = match slot {
+                Slot::Occupied { value, .. } => Some(value),
+                Slot::Vacant { .. } => unreachable!("link to a vacant slot"),
+            };
+        }
+        values.into_iter().map(Option::unwrap).collect()
+    }
+
+    fn alloc(&mut self, value: T, prev: Link, next: Link) -> u32 {
+        let slot = Slot::Occupied { value, prev, next };
+        let index = match self.free {
+            Some(index) => {
+                let old = mem::replace(&mut self.slots[index as usize], slot);
+                match old {
+                    Slot::Vacant { next_free } => self.free = next_free,
+                    Slot::Occupied { .. } => unreachable!("free list points at an occupied slot"),
+                }
+                index
+            }
+            None => {
+                let index = u32::try_from(self.slots.len())
+                    .ok()
+                    .filter(|&index| index != u32::MAX)
+                    .expect("ArenaList cannot hold more than u32::MAX - 1 nodes");
+                self.slots.push(slot);
+                index
+            }
+        };
+        self.len += 1;
+        index
+    }
+
+    fn unlink(&mut self, node: u32) -> T {
+        let vacant = Slot::Vacant {
+            next_free: self.free,
+        };
+        let (value, prev, next) = match mem::replace(&mut self.slots[node as usize], vacant) {
+            Slot::Occupied { value, prev, next } => (value, prev, next),
+            Slot::Vacant { .. } => unreachable!("unlinking a vacant slot"),
+        };
+        self.free = Some(node);
+        self.len -= 1;
+
+        match prev {
+            Some(prev) => *self.next_mut(prev) = next,
+            None => self.first = next,
+        }
+        match next {
+            Some(next) => *self.prev_mut(next) = prev,
+            None => self.last = prev,
+        }
+        value
+    }
+
+    fn links(&self, node: u32) -> (Link, Link) {
+        match &self.slots[node as usize] {
+            Slot::Occupied { prev, next, .. } => (*prev, *next),
+            Slot::Vacant { .. } => unreachable!("link to a vacant slot"),
+        }
+    }
+
+    fn prev_mut(&mut self, node: u32) -> &mut Link {
+        match &mut self.slots[node as usize] {
+            Slot::Occupied { prev, .. } => prev,
+            Slot::Vacant { .. } => unreachable!("link to a vacant slot"),
+        }
+    }
+
+    fn next_mut(&mut self, node: u32) -> &mut Link {
+        match &mut self.slots[node as usize] {
+            Slot::Occupied { next, .. } => next,
+            Slot::Vacant { .. } => unreachable!("link to a vacant slot"),
+        }
+    }
+
+    fn value(&self, node: u32) -> &T {
+        match &self.slots[node as usize] {
+            Slot::Occupied { value, .. } => value,
+            Slot::Vacant { .. } => unreachable!("link to a vacant slot"),
+        }
+    }
+
+    fn value_mut(&mut self, node: u32) -> &mut T {
+        match &mut self.slots[node as usize] {
+            Slot::Occupied { value, .. } => value,
+            Slot::Vacant { .. } => unreachable!("link to a vacant slot"),
+        }
+    }
+
+    fn node_at(&self, index: usize) -> Link {
+        if index >= self.len {
+            return None;
+        }
+        if index < self.len / 2 {
+            let mut cur = self.first;
+            for _ in 0..index {
+                cur = self.links(cur?).1;
+            }
+            cur
+        } else {
+            let mut cur = self.last;
+            for _ in index + 1..self.len {
+                cur = self.links(cur?).0;
+            }
+            cur
+        }
+    }
+}
+
+impl<T> Default for ArenaList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for ArenaList<T> {
+    fn clone(&self) -> Self {
+        let mut new_list = Self::with_capacity(self.len);
+        for elem in self {
+            new_list.push_back(elem.clone());
+        }
+        new_list
+    }
+}
+
+impl<T> Extend<T> for ArenaList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for ArenaList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for ArenaList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArenaList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for ArenaList<T> {}
+
+impl<T: PartialOrd> PartialOrd for ArenaList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for ArenaList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for ArenaList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ArenaList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut ArenaList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> IntoIterator for ArenaList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.first?;
+        match &self.slots[node as usize] {
+            Slot::Occupied { value, next, .. } => {
+                self.len -= 1;
+                self.first = *next;
+                Some(value)
+            }
+            Slot::Vacant { .. } => unreachable!("link to a vacant slot"),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.last?;
+        match &self.slots[node as usize] {
+            Slot::Occupied { value, prev, .. } => {
+                self.len -= 1;
+                self.last = *prev;
+                Some(value)
+            }
+            Slot::Vacant { .. } => unreachable!("link to a vacant slot"),
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.values.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.values.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_first()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {
+    fn len(&self) -> usize {
+        self.list.len
+    }
+}
+
+impl<'a, T> Cursor<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn seek(&mut self, index: usize) {
+        self.cur = self.list.node_at(index);
+        self.index = self.cur.map(|_| index);
+    }
+
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = self.list.links(cur).1;
+            if self.cur.is_some() {
+                *self.index.as_mut().unwrap() += 1;
+            } else {
+                self.index = None;
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.first;
+            self.index = Some(0);
+        }
+    }
+
+    pub fn move_back(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = self.list.links(cur).0;
+            if self.cur.is_some() {
+                *self.index.as_mut().unwrap() -= 1;
+            } else {
+                self.index = None;
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.last;
+            self.index = Some(self.list.len - 1);
+        }
+    }
+
+    pub fn current(&self) -> Option<&'a T> {
+        let list = self.list;
+        self.cur.map(|node| list.value(node))
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let list = self.list;
+        let next = match self.cur {
+            Some(cur) => list.links(cur).1,
+            None => list.first,
+        };
+        next.map(|node| list.value(node))
+    }
+
+    pub fn peek_back(&self) -> Option<&'a T> {
+        let list = self.list;
+        let prev = match self.cur {
+            Some(cur) => list.links(cur).0,
+            None => list.last,
+        };
+        prev.map(|node| list.value(node))
+    }
+}
+
+impl<T> Clone for Cursor<'_, T> {
+    fn clone(&self) -> Self {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+            index: self.index,
+        }
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+            index: self.index,
+        }
+    }
+
+    pub fn seek(&mut self, index: usize) {
+        self.cur = self.list.node_at(index);
+        self.index = self.cur.map(|_| index);
+    }
+
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = self.list.links(cur).1;
+            if self.cur.is_some() {
+                *self.index.as_mut().unwrap() += 1;
+            } else {
+                self.index = None;
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.first;
+            self.index = Some(0);
+        }
+    }
+
+    pub fn move_back(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = self.list.links(cur).0;
+            if self.cur.is_some() {
+                *self.index.as_mut().unwrap() -= 1;
+            } else {
+                self.index = None;
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.last;
+            self.index = Some(self.list.len - 1);
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.cur.map(|node| self.list.value_mut(node))
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = match self.cur {
+            Some(cur) => self.list.links(cur).1,
+            None => self.list.first,
+        };
+        next.map(|node| self.list.value_mut(node))
+    }
+
+    pub fn peek_back(&mut self) -> Option<&mut T> {
+        let prev = match self.cur {
+            Some(cur) => self.list.links(cur).0,
+            None => self.list.last,
+        };
+        prev.map(|node| self.list.value_mut(node))
+    }
+
+    pub fn insert_before(&mut self, value: T) {
+        match self.cur {
+            Some(cur) => {
+                let prev = self.list.links(cur).0;
+                let new = self.list.alloc(value, prev, Some(cur));
+                *self.list.prev_mut(cur) = Some(new);
+                match prev {
+                    Some(prev) => *self.list.next_mut(prev) = Some(new),
+                    None => self.list.first = Some(new),
+                }
+                *self.index.as_mut().unwrap() += 1;
+            }
+            None => self.list.push_back(value),
+        }
+    }
+
+    pub fn insert_after(&mut self, value: T) {
+        match self.cur {
+            Some(cur) => {
+                let next = self.list.links(cur).1;
+                let new = self.list.alloc(value, Some(cur), next);
+                *self.list.next_mut(cur) = Some(new);
+                match next {
+                    Some(next) => *self.list.prev_mut(next) = Some(new),
+                    None => self.list.last = Some(new),
+                }
+            }
+            None => self.list.push(value),
+        }
+    }
+
+    pub fn replace_current(&mut self, value: T) -> Result<T, T> {
+        match self.current() {
+            Some(current) => Ok(mem::replace(current, value)),
+            None => Err(value),
+        }
+    }
+
+    pub fn remove_current(&mut self) -> Option<T> {
+        let cur = self.cur?;
+        let next = self.list.links(cur).1;
+        let value = self.list.unlink(cur);
+        self.cur = next;
+        if next.is_none() {
+            self.index = None;
+        }
+        Some(value)
+    }
+
+    pub fn remove_current_as_list(&mut self) -> Option<ArenaList<T>> {
+        let value = self.remove_current()?;
+        let mut list = ArenaList::with_capacity(1);
+        list.push(value);
+        Some(list)
+    }
+
+    // Lists don't share a slab, so splicing and splitting move every element that changes list:
+    // O(input.len()) to splice, O(elements split off) to split
+
+    pub fn splice_before(&mut self, input: ArenaList<T>) {
+        if self.list.is_empty() {
+            *self.list = input;
+            return;
+        }
+        for value in input {
+            self.insert_before(value);
+        }
+    }
+
+    pub fn splice_after(&mut self, input: ArenaList<T>) {
+        if self.list.is_empty() {
+            *self.list = input;
+            return;
+        }
+        for value in input.into_iter().rev() {
+            self.insert_after(value);
+        }
+    }
+
+    pub fn split_before(&mut self) -> ArenaList<T> {
+        match self.cur {
+            Some(cur) => {
+                let mut front = ArenaList::with_capacity(self.index.unwrap());
+                while let Some(prev) = self.list.links(cur).0 {
+                    front.push(self.list.unlink(prev));
+                }
+                self.index = Some(0);
+                front
+            }
+            None => mem::take(self.list),
+        }
+    }
+
+    pub fn split_after(&mut self) -> ArenaList<T> {
+        match self.cur {
+            Some(cur) => {
+                let mut back = ArenaList::with_capacity(self.list.len - self.index.unwrap() - 1);
+                while let Some(next) = self.list.links(cur).1 {
+                    back.push_back(self.list.unlink(next));
+                }
+                back
+            }
+            None => mem::take(self.list),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ArenaList;
+    use crate::rng::SplitMix64;
+    use std::collections::VecDeque;
+
+    fn generate_test() -> ArenaList<i32> {
+        list_from(&[0, 1, 2, 3, 4, 5, 6])
+    }
+
+    fn list_from<T: Clone>(data: &[T]) -> ArenaList<T> {
+        data.iter().map(|v| (*v).clone()).collect()
+    }
+
+    fn check_links<T>(list: &ArenaList<T>) {
+        let mut len = 0;
+        let mut prev = None;
+        let mut cur = list.first;
+        while let Some(node) = cur {
+            let (node_prev, node_next) = list.links(node);
+            assert_eq!(node_prev, prev);
+            prev = cur;
+            cur = node_next;
+            len += 1;
+        }
+        assert_eq!(list.last, prev);
+        assert_eq!(list.len, len);
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut m = ArenaList::new();
+        assert_eq!(m.pop_first(), None);
+        assert_eq!(m.pop_back(), None);
+        m.push(1);
+        assert_eq!(m.pop_first(), Some(1));
+        m.push_back(2);
+        m.push_back(3);
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.pop_first(), Some(2));
+        assert_eq!(m.pop_first(), Some(3));
+        assert_eq!(m.len(), 0);
+        assert_eq!(m.pop_first(), None);
+        m.push_back(1);
+        m.push_back(3);
+        m.push_back(5);
+        m.push(7);
+        check_links(&m);
+        assert_eq!(m.pop_first(), Some(7));
+        assert_eq!(m.pop_back(), Some(5));
+
+        let mut n = ArenaList::new();
+        n.push(2);
+        n.push(3);
+        {
+            assert_eq!(n.first().unwrap(), &3);
+            let x = n.first_mut().unwrap();
+            assert_eq!(*x, 3);
+            *x = 0;
+        }
+        {
+            assert_eq!(n.back().unwrap(), &2);
+            let y = n.back_mut().unwrap();
+            assert_eq!(*y, 2);
+            *y = 1;
+        }
+        assert_eq!(n.pop_first(), Some(0));
+        assert_eq!(n.pop_first(), Some(1));
+    }
+
+    #[test]
+    fn test_slot_reuse() {
+        let mut m = ArenaList::with_capacity(4);
+        for round in 0..100 {
+            m.push_back(round);
+            m.push(round);
+            m.push_back(round);
+            assert_eq!(m.pop_first(), Some(round));
+            assert_eq!(m.pop_back(), Some(round));
+            assert_eq!(m.pop_back(), Some(round));
+        }
+        assert!(m.slots.len() <= 3);
+        check_links(&m);
+
+        m.extend(0..3);
+        assert_eq!(m.slots.len(), 3);
+        m.clear();
+        assert!(m.is_empty());
+        assert_eq!(m.first(), None);
+        m.push(9);
+        check_links(&m);
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut m = generate_test();
+        for (i, elt) in m.iter().enumerate() {
+            assert_eq!(i as i32, *elt);
+        }
+        for (i, elt) in m.iter().rev().enumerate() {
+            assert_eq!(6 - i as i32, *elt);
+        }
+
+        let mut it = m.iter();
+        assert_eq!(it.size_hint(), (7, Some(7)));
+        assert_eq!(it.next(), Some(&0));
+        assert_eq!(it.next_back(), Some(&6));
+        assert_eq!(it.len(), 5);
+
+        for elt in m.iter_mut() {
+            *elt *= 10;
+        }
+        let mut it = m.iter_mut();
+        assert_eq!(it.next_back(), Some(&mut 60));
+        assert_eq!(it.next(), Some(&mut 0));
+        assert_eq!(it.len(), 5);
+        drop(it);
+
+        let mut it = m.clone().into_iter();
+        assert_eq!(it.next_back(), Some(60));
+        assert_eq!(it.next(), Some(0));
+        assert_eq!(it.len(), 5);
+
+        // Iteration follows links, not slot order
+        let mut n = ArenaList::new();
+        n.push_back(2);
+        n.push(1);
+        n.push_back(3);
+        n.pop_first();
+        n.push(0);
+        assert_eq!(n.iter().copied().collect::<Vec<_>>(), [0, 2, 3]);
+        assert_eq!(
+            n.iter_mut().rev().map(|x| *x).collect::<Vec<_>>(),
+            [3, 2, 0]
+        );
+    }
+
+    #[test]
+    fn test_iter_mut_sparse() {
+        let mut rng = SplitMix64::new(3);
+        let mut m = ArenaList::new();
+        let mut model = VecDeque::new();
+        for i in 0..500 {
+            match rng.next_u64() % 4 {
+                0 => {
+                    m.push(i);
+                    model.push_front(i);
+                }
+                1 => {
+                    m.push_back(i);
+                    model.push_back(i);
+                }
+                2 => assert_eq!(m.pop_first(), model.pop_front()),
+                _ => assert_eq!(m.pop_back(), model.pop_back()),
+            }
+        }
+        // Some slots vacant, but few enough that iter_mut splits slot by slot
+        assert!(m.len() < m.slots.len() && m.slots.len() <= 2 * m.len());
+
+        // Take from both ends at random
+        let mut it = m.iter_mut();
+        let mut seen = VecDeque::new();
+        let (mut front, mut back) = (Vec::new(), Vec::new());
+        while it.len() > 0 {
+            if rng.next_u64().is_multiple_of(2) {
+                front.push(it.next().unwrap());
+            } else {
+                back.push(it.next_back().unwrap());
+            }
+        }
+        assert!(it.next().is_none() && it.next_back().is_none());
+        for value in front.into_iter().chain(back.into_iter().rev()) {
+            *value += 1000;
+            seen.push_back(*value - 1000);
+        }
+        assert_eq!(seen, model);
+        check_links(&m);
+
+        // Mostly vacant, so only the live slots get sorted and split
+        let mut n: ArenaList<_> = (0..100).collect();
+        for _ in 0..90 {
+            n.pop_first();
+        }
+        n.push(-1);
+        n.push_back(100);
+        assert!(n.slots.len() > 2 * n.len());
+        for value in n.iter_mut().rev() {
+            *value *= 2;
+        }
+        let expected = [-1].into_iter().chain(90..=100).map(|x| x * 2);
+        assert!(n.iter().copied().eq(expected));
+    }
+
+    #[test]
+    fn test_traits() {
+        let n = list_from(&[1, 2, 3]);
+        let m = n.clone();
+        assert_eq!(n, m);
+        assert!(list_from(&[]) < m);
+        assert!(list_from(&[1, 2, 4]) > m);
+        assert_eq!(format!("{:?}", m), "[1, 2, 3]");
+        assert_eq!(ArenaList::<i32>::default(), list_from(&[]));
+
+        let mut map = std::collections::HashMap::new();
+        map.insert(n, "n");
+        assert_eq!(map.get(&m), Some(&"n"));
+    }
+
+    #[test]
+    fn test_cursor() {
+        let m = generate_test();
+        let mut cursor = m.cursor();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&0));
+        assert_eq!(cursor.peek_next(), Some(&1));
+        assert_eq!(cursor.peek_back(), None);
+        cursor.move_back();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.peek_back(), Some(&6));
+        cursor.move_back();
+        assert_eq!(cursor.index(), Some(6));
+        for i in 0..7 {
+            cursor.seek(i);
+            assert_eq!(cursor.current(), Some(&(i as i32)));
+        }
+        cursor.seek(7);
+        assert_eq!(cursor.current(), None);
+    }
+
+    #[test]
+    fn test_cursor_mut_edit() {
+        let mut m = list_from(&[1, 2, 3]);
+        let mut cursor = m.cursor_mut();
+        cursor.insert_before(4);
+        cursor.insert_after(0);
+        assert_eq!(cursor.replace_current(9), Err(9));
+
+        cursor.seek(1);
+        assert_eq!(cursor.current(), Some(&mut 1));
+        cursor.insert_before(10);
+        assert_eq!(cursor.index(), Some(2));
+        cursor.insert_after(11);
+        assert_eq!(cursor.replace_current(12), Ok(1));
+        assert_eq!(cursor.as_cursor().peek_next(), Some(&11));
+        check_links(&m);
+        assert_eq!(
+            m.iter().copied().collect::<Vec<_>>(),
+            [0, 10, 12, 11, 2, 3, 4]
+        );
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        while let Some(value) = cursor.current() {
+            if *value % 2 == 0 {
+                cursor.remove_current();
+            } else {
+                cursor.move_next();
+            }
+        }
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), [11, 3]);
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_back();
+        assert_eq!(cursor.remove_current(), Some(3));
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(11));
+        assert!(m.is_empty());
+        check_links(&m);
+    }
+
+    #[test]
+    fn test_cursor_mut_split_splice() {
+        let mut m = generate_test();
+        let mut cursor = m.cursor_mut();
+        cursor.seek(3);
+        let front = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        let back = cursor.split_after();
+        assert_eq!(cursor.index(), Some(0));
+        check_links(&front);
+        check_links(&back);
+        assert_eq!(front.iter().copied().collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(back.iter().copied().collect::<Vec<_>>(), [4, 5, 6]);
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.splice_before(front);
+        assert_eq!(cursor.index(), Some(3));
+        cursor.splice_after(back);
+        assert_eq!(cursor.index(), Some(3));
+        assert_eq!(cursor.peek_next(), Some(&mut 4));
+        check_links(&m);
+        assert_eq!(m, generate_test());
+
+        // At the ghost element splicing appends or prepends and splitting takes everything
+        let mut cursor = m.cursor_mut();
+        cursor.splice_before(list_from(&[7]));
+        cursor.splice_after(list_from(&[-1]));
+        cursor.splice_after(ArenaList::new());
+        assert_eq!(cursor.split_before().len(), 9);
+        assert!(cursor.split_after().is_empty());
+        cursor.splice_after(list_from(&[1, 2]));
+        cursor.splice_before(list_from(&[3]));
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+
+        // Splitting at either end leaves one side empty
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        assert!(cursor.split_before().is_empty());
+        cursor.move_back();
+        cursor.move_back();
+        assert!(cursor.split_after().is_empty());
+        assert_eq!(cursor.index(), Some(2));
+
+        let mut cursor = m.cursor_mut();
+        cursor.seek(1);
+        let removed = cursor.remove_current_as_list().unwrap();
+        assert_eq!(removed.iter().copied().collect::<Vec<_>>(), [2]);
+        assert_eq!(cursor.current(), Some(&mut 3));
+        cursor.move_next();
+        assert!(cursor.remove_current_as_list().is_none());
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), [1, 3]);
+    }
+}
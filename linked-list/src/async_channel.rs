@@ -0,0 +1,523 @@
+use crate::unsafe_queue::UnsafeQueue;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+pub struct Sender<T> {
+    shared: Arc<Mutex<State<T>>>,
+}
+
+pub struct Receiver<T> {
+    shared: Arc<Mutex<State<T>>>,
+}
+
+pub struct SendFuture<'a, T> {
+    sender: &'a Sender<T>,
+    value: Option<T>,
+}
+
+pub struct RecvFuture<'a, T> {
+    receiver: &'a mut Receiver<T>,
+}
+
+struct State<T> {
+    queue: UnsafeQueue<T>,
+    len: usize,
+    capacity: Option<usize>,
+    senders: usize,
+    closed: bool,
+    recv_waker: Option<Waker>,
+    send_wakers: Vec<Waker>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    Full(T),
+    Closed(T),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    Empty,
+    Closed,
+}
+
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "bounded channel capacity must be non-zero");
+    with_capacity(Some(capacity))
+}
+
+pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
+    with_capacity(None)
+}
+
+fn with_capacity<T>(capacity: Option<usize>) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Mutex::new(State {
+        queue: UnsafeQueue::new(),
+        len: 0,
+        capacity,
+        senders: 1,
+        closed: false,
+        recv_waker: None,
+        send_wakers: Vec::new(),
+    }));
+    (
+        Sender {
+            shared: Arc::clone(&shared),
+        },
+        Receiver { shared },
+    )
+}
+
+fn lock<T>(shared: &Mutex<State<T>>) -> MutexGuard<'_, State<T>> {
+    shared.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<T> State<T> {
+    fn is_full(&self) -> bool {
+        self.capacity.is_some_and(|capacity| self.len >= capacity)
+    }
+
+    // Wakers are taken out under the lock but only woken after it is released, so a task that
+    // polls straight away on another thread does not block on the channel
+    fn take_recv_waker(&mut self) -> Option<Waker> {
+        self.recv_waker.take()
+    }
+
+    fn take_send_wakers(&mut self) -> Vec<Waker> {
+        mem::take(&mut self.send_wakers)
+    }
+}
+
+impl<T> Sender<T> {
+    pub fn send(&self, value: T) -> SendFuture<'_, T> {
+        SendFuture {
+            sender: self,
+            value: Some(value),
+        }
+    }
+
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        let mut state = lock(&self.shared);
+        if state.closed {
+            return Err(TrySendError::Closed(value));
+        }
+        if state.is_full() {
+            return Err(TrySendError::Full(value));
+        }
+        state.queue.push(value);
+        state.len += 1;
+        let waker = state.take_recv_waker();
+        drop(state);
+        waker.into_iter().for_each(Waker::wake);
+        Ok(())
+    }
+
+    pub fn is_closed(&self) -> bool {
+        lock(&self.shared).closed
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        lock(&self.shared).senders += 1;
+        Sender {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = lock(&self.shared);
+        state.senders -= 1;
+        if state.senders == 0 {
+            let waker = state.take_recv_waker();
+            drop(state);
+            waker.into_iter().for_each(Waker::wake);
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    pub fn recv(&mut self) -> RecvFuture<'_, T> {
+        RecvFuture { receiver: self }
+    }
+
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let mut state = lock(&self.shared);
+        match state.queue.pop() {
+            Some(value) => {
+                state.len -= 1;
+                let wakers = state.take_send_wakers();
+                drop(state);
+                wakers.into_iter().for_each(Waker::wake);
+                Ok(value)
+            }
+            None if state.closed || state.senders == 0 => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        match self.try_recv() {
+            Ok(value) => Poll::Ready(Some(value)),
+            Err(TryRecvError::Closed) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => {
+                let mut state = lock(&self.shared);
+                // A sender may have slipped in between try_recv and taking the lock again
+                if state.len > 0 || state.senders == 0 {
+                    drop(state);
+                    return self.poll_recv(cx);
+                }
+                state.recv_waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    pub fn close(&mut self) {
+        let mut state = lock(&self.shared);
+        state.closed = true;
+        let wakers = state.take_send_wakers();
+        drop(state);
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    pub fn is_closed(&self) -> bool {
+        let state = lock(&self.shared);
+        state.closed || state.senders == 0
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+// The value is only ever moved, never pinned, so the future can be Unpin for any T
+impl<T> Unpin for SendFuture<'_, T> {}
+
+impl<T> Future for SendFuture<'_, T> {
+    type Output = Result<(), SendError<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let value = this
+            .value
+            .take()
+            .expect("SendFuture polled after completion");
+
+        match this.sender.try_send(value) {
+            Ok(()) => Poll::Ready(Ok(())),
+            Err(TrySendError::Closed(value)) => Poll::Ready(Err(SendError(value))),
+            Err(TrySendError::Full(value)) => {
+                let mut state = lock(&this.sender.shared);
+                if state.closed || !state.is_full() {
+                    drop(state);
+                    this.value = Some(value);
+                    return Pin::new(this).poll(cx);
+                }
+                if !state.send_wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                    state.send_wakers.push(cx.waker().clone());
+                }
+                this.value = Some(value);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Future for RecvFuture<'_, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().receiver.poll_recv(cx)
+    }
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SendError(..)")
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("channel is closed")
+    }
+}
+
+impl<T> Error for SendError<T> {}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.write_str("Full(..)"),
+            TrySendError::Closed(_) => f.write_str("Closed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.write_str("channel is full"),
+            TrySendError::Closed(_) => f.write_str("channel is closed"),
+        }
+    }
+}
+
+impl<T> Error for TrySendError<T> {}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => f.write_str("channel is empty"),
+            TryRecvError::Closed => f.write_str("channel is closed and drained"),
+        }
+    }
+}
+
+impl Error for TryRecvError {}
+
+#[cfg(test)]
+mod test {
+    use super::{channel, unbounded, SendError, State, TryRecvError, TrySendError};
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::{self, Thread};
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    // Counts the wakes that found the channel unlocked
+    struct LockProbe {
+        shared: Arc<Mutex<State<i32>>>,
+        unlocked: AtomicUsize,
+    }
+
+    impl Wake for LockProbe {
+        fn wake(self: Arc<Self>) {
+            if self.shared.try_lock().is_ok() {
+                self.unlocked.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn basics() {
+        let (tx, mut rx) = unbounded();
+        block_on(async {
+            tx.send(1).await.unwrap();
+            tx.send(2).await.unwrap();
+            assert_eq!(rx.recv().await, Some(1));
+            tx.send(3).await.unwrap();
+            assert_eq!(rx.recv().await, Some(2));
+            assert_eq!(rx.recv().await, Some(3));
+            assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        });
+    }
+
+    #[test]
+    fn recv_wakes_on_send() {
+        let (tx, mut rx) = unbounded();
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(Arc::clone(&counter));
+        let mut cx = Context::from_waker(&waker);
+
+        let mut recv = pin!(rx.recv());
+        assert_eq!(recv.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+
+        tx.try_send(7).unwrap();
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        assert_eq!(recv.as_mut().poll(&mut cx), Poll::Ready(Some(7)));
+    }
+
+    #[test]
+    fn wakers_run_without_the_lock() {
+        let (tx, mut rx) = channel(1);
+        let probe = Arc::new(LockProbe {
+            shared: Arc::clone(&tx.shared),
+            unlocked: AtomicUsize::new(0),
+        });
+        let waker = Waker::from(Arc::clone(&probe));
+        let mut cx = Context::from_waker(&waker);
+
+        // Receiver woken by a send
+        assert_eq!(rx.poll_recv(&mut cx), Poll::Pending);
+        tx.try_send(1).unwrap();
+        assert_eq!(probe.unlocked.load(Ordering::SeqCst), 1);
+
+        // Sender woken by a receive
+        let mut send = pin!(tx.send(2));
+        assert_eq!(send.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert_eq!(probe.unlocked.load(Ordering::SeqCst), 2);
+        assert_eq!(send.as_mut().poll(&mut cx), Poll::Ready(Ok(())));
+
+        // Sender woken by close, receiver woken by the last sender going away
+        let mut send = pin!(tx.send(3));
+        assert_eq!(send.as_mut().poll(&mut cx), Poll::Pending);
+        rx.close();
+        assert_eq!(probe.unlocked.load(Ordering::SeqCst), 3);
+
+        let (tx, mut rx) = unbounded::<i32>();
+        let probe = Arc::new(LockProbe {
+            shared: Arc::clone(&tx.shared),
+            unlocked: AtomicUsize::new(0),
+        });
+        let waker = Waker::from(Arc::clone(&probe));
+        assert_eq!(
+            rx.poll_recv(&mut Context::from_waker(&waker)),
+            Poll::Pending
+        );
+        drop(tx);
+        assert_eq!(probe.unlocked.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn bounded_send_waits_for_space() {
+        let (tx, mut rx) = channel(1);
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(Arc::clone(&counter));
+        let mut cx = Context::from_waker(&waker);
+
+        assert_eq!(tx.try_send(1), Ok(()));
+        assert_eq!(tx.try_send(2), Err(TrySendError::Full(2)));
+
+        let mut send = pin!(tx.send(2));
+        assert_eq!(send.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(send.as_mut().poll(&mut cx), Poll::Pending);
+
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        assert_eq!(send.as_mut().poll(&mut cx), Poll::Ready(Ok(())));
+        assert_eq!(rx.try_recv(), Ok(2));
+    }
+
+    #[test]
+    fn close_detection() {
+        let (tx, mut rx) = channel::<i32>(4);
+        let tx2 = tx.clone();
+        block_on(tx.send(1)).unwrap();
+        drop(tx);
+        assert!(!rx.is_closed());
+
+        block_on(tx2.send(2)).unwrap();
+        drop(tx2);
+        assert!(rx.is_closed());
+
+        // Buffered values survive the senders going away
+        assert_eq!(block_on(rx.recv()), Some(1));
+        assert_eq!(block_on(rx.recv()), Some(2));
+        assert_eq!(block_on(rx.recv()), None);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+
+        let (tx, mut rx) = unbounded();
+        rx.close();
+        assert!(tx.is_closed());
+        assert_eq!(block_on(tx.send(3)), Err(SendError(3)));
+
+        let (tx, rx) = unbounded::<i32>();
+        drop(rx);
+        assert_eq!(tx.try_send(4), Err(TrySendError::Closed(4)));
+    }
+
+    #[test]
+    fn closing_wakes_pending_futures() {
+        let (tx, rx) = channel(1);
+        tx.try_send(0).unwrap();
+
+        let sender = thread::spawn(move || block_on(tx.send(1)));
+        while rx.shared.lock().unwrap().send_wakers.is_empty() {
+            thread::yield_now();
+        }
+        drop(rx);
+        assert_eq!(sender.join().unwrap(), Err(SendError(1)));
+
+        let (tx, mut rx) = unbounded::<i32>();
+        let receiver = thread::spawn(move || block_on(rx.recv()));
+        while tx.shared.lock().unwrap().recv_waker.is_none() {
+            thread::yield_now();
+        }
+        drop(tx);
+        assert_eq!(receiver.join().unwrap(), None);
+    }
+
+    #[test]
+    fn producers_across_threads() {
+        const PRODUCERS: usize = 4;
+        const PER_PRODUCER: usize = 500;
+
+        let (tx, mut rx) = channel(3);
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|producer| {
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    block_on(async {
+                        for seq in 0..PER_PRODUCER {
+                            tx.send((producer, seq)).await.unwrap();
+                        }
+                    })
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let received = block_on(async {
+            let mut received = Vec::new();
+            while let Some(item) = rx.recv().await {
+                received.push(item);
+            }
+            received
+        });
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        let mut last = [None; PRODUCERS];
+        for &(producer, seq) in &received {
+            assert!(last[producer] < Some(seq));
+            last[producer] = Some(seq);
+        }
+        assert_eq!(received.len(), PRODUCERS * PER_PRODUCER);
+    }
+}
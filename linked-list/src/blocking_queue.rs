@@ -0,0 +1,342 @@
+use crate::unsafe_queue::UnsafeQueue;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+pub struct BlockingQueue<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    capacity: usize,
+}
+
+struct State<T> {
+    queue: UnsafeQueue<T>,
+    len: usize,
+    closed: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PutError<T> {
+    Full(T),
+    Closed(T),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TakeError {
+    Empty,
+    Closed,
+}
+
+impl<T> BlockingQueue<T> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "BlockingQueue capacity must be non-zero");
+        BlockingQueue {
+            state: Mutex::new(State {
+                queue: UnsafeQueue::new(),
+                len: 0,
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            capacity,
+        }
+    }
+
+    pub fn put(&self, value: T) -> Result<(), PutError<T>> {
+        let state = self.lock();
+        let state = self
+            .not_full
+            .wait_while(state, |state| !state.closed && state.len == self.capacity)
+            .unwrap_or_else(PoisonError::into_inner);
+        self.push_locked(state, value)
+    }
+
+    pub fn offer(&self, value: T, timeout: Duration) -> Result<(), PutError<T>> {
+        let state = self.lock();
+        let (state, _) = self
+            .not_full
+            .wait_timeout_while(state, timeout, |state| {
+                !state.closed && state.len == self.capacity
+            })
+            .unwrap_or_else(PoisonError::into_inner);
+        self.push_locked(state, value)
+    }
+
+    pub fn take(&self) -> Result<T, TakeError> {
+        let state = self.lock();
+        let state = self
+            .not_empty
+            .wait_while(state, |state| !state.closed && state.len == 0)
+            .unwrap_or_else(PoisonError::into_inner);
+        self.pop_locked(state)
+    }
+
+    pub fn poll(&self, timeout: Duration) -> Result<T, TakeError> {
+        let state = self.lock();
+        let (state, _) = self
+            .not_empty
+            .wait_timeout_while(state, timeout, |state| !state.closed && state.len == 0)
+            .unwrap_or_else(PoisonError::into_inner);
+        self.pop_locked(state)
+    }
+
+    pub fn drain_to(&self, out: &mut Vec<T>) -> usize {
+        let mut state = self.lock();
+        let drained = state.len;
+        out.reserve(drained);
+        while let Some(value) = state.queue.pop() {
+            out.push(value);
+        }
+        state.len = 0;
+        drop(state);
+
+        self.not_full.notify_all();
+        drained
+    }
+
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        // The state is never left half-updated, so a panic elsewhere cannot corrupt it
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push_locked(
+        &self,
+        mut state: MutexGuard<'_, State<T>>,
+        value: T,
+    ) -> Result<(), PutError<T>> {
+        if state.closed {
+            return Err(PutError::Closed(value));
+        }
+        if state.len == self.capacity {
+            return Err(PutError::Full(value));
+        }
+        state.queue.push(value);
+        state.len += 1;
+        drop(state);
+
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    fn pop_locked(&self, mut state: MutexGuard<'_, State<T>>) -> Result<T, TakeError> {
+        match state.queue.pop() {
+            Some(value) => {
+                state.len -= 1;
+                drop(state);
+
+                self.not_full.notify_one();
+                Ok(value)
+            }
+            None if state.closed => Err(TakeError::Closed),
+            None => Err(TakeError::Empty),
+        }
+    }
+}
+
+impl<T> PutError<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            PutError::Full(value) | PutError::Closed(value) => value,
+        }
+    }
+}
+
+impl<T> fmt::Debug for PutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PutError::Full(_) => f.write_str("Full(..)"),
+            PutError::Closed(_) => f.write_str("Closed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for PutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PutError::Full(_) => f.write_str("queue is full"),
+            PutError::Closed(_) => f.write_str("queue is closed"),
+        }
+    }
+}
+
+impl<T> Error for PutError<T> {}
+
+impl fmt::Display for TakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TakeError::Empty => f.write_str("queue is empty"),
+            TakeError::Closed => f.write_str("queue is closed and drained"),
+        }
+    }
+}
+
+impl Error for TakeError {}
+
+#[cfg(test)]
+mod test {
+    use super::{BlockingQueue, PutError, TakeError};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    const SHORT: Duration = Duration::from_millis(20);
+
+    #[test]
+    fn basics() {
+        let queue = BlockingQueue::new(3);
+        assert!(queue.is_empty());
+        assert_eq!(queue.capacity(), 3);
+
+        queue.put(1).unwrap();
+        queue.put(2).unwrap();
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.take(), Ok(1));
+        queue.put(3).unwrap();
+        assert_eq!(queue.take(), Ok(2));
+        assert_eq!(queue.take(), Ok(3));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn timeouts() {
+        let queue = BlockingQueue::new(2);
+        assert_eq!(queue.poll(SHORT), Err(TakeError::Empty));
+
+        queue.offer(1, SHORT).unwrap();
+        queue.offer(2, SHORT).unwrap();
+        assert_eq!(queue.offer(3, SHORT), Err(PutError::Full(3)));
+        assert_eq!(queue.offer(3, Duration::ZERO).unwrap_err().into_inner(), 3);
+
+        assert_eq!(queue.poll(SHORT), Ok(1));
+        queue.offer(3, Duration::ZERO).unwrap();
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[test]
+    fn close() {
+        let queue = BlockingQueue::new(4);
+        queue.put(1).unwrap();
+        queue.put(2).unwrap();
+        queue.close();
+        assert!(queue.is_closed());
+
+        assert_eq!(queue.put(3), Err(PutError::Closed(3)));
+        assert_eq!(queue.offer(4, SHORT), Err(PutError::Closed(4)));
+
+        // Values already queued can still be taken
+        assert_eq!(queue.take(), Ok(1));
+        assert_eq!(queue.poll(SHORT), Ok(2));
+        assert_eq!(queue.take(), Err(TakeError::Closed));
+        assert_eq!(queue.poll(SHORT), Err(TakeError::Closed));
+    }
+
+    #[test]
+    fn close_wakes_blocked_threads() {
+        let empty = Arc::new(BlockingQueue::<i32>::new(1));
+        let full = Arc::new(BlockingQueue::new(1));
+        full.put(0).unwrap();
+
+        let taker = {
+            let empty = Arc::clone(&empty);
+            thread::spawn(move || empty.take())
+        };
+        let putter = {
+            let full = Arc::clone(&full);
+            thread::spawn(move || full.put(1))
+        };
+
+        thread::sleep(SHORT);
+        empty.close();
+        full.close();
+
+        assert_eq!(taker.join().unwrap(), Err(TakeError::Closed));
+        assert_eq!(putter.join().unwrap(), Err(PutError::Closed(1)));
+    }
+
+    #[test]
+    fn drain_to() {
+        let queue = BlockingQueue::new(3);
+        let mut out = vec![0];
+        assert_eq!(queue.drain_to(&mut out), 0);
+
+        queue.put(1).unwrap();
+        queue.put(2).unwrap();
+        queue.put(3).unwrap();
+        assert_eq!(queue.drain_to(&mut out), 3);
+        assert_eq!(out, [0, 1, 2, 3]);
+        assert!(queue.is_empty());
+
+        // Draining frees capacity for new values
+        queue.offer(4, Duration::ZERO).unwrap();
+        assert_eq!(queue.take(), Ok(4));
+    }
+
+    #[test]
+    fn producers_and_consumers() {
+        const PRODUCERS: usize = 4;
+        const PER_PRODUCER: usize = 1_000;
+
+        let queue = Arc::new(BlockingQueue::new(8));
+
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|producer| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    for seq in 0..PER_PRODUCER {
+                        queue.put(producer * PER_PRODUCER + seq).unwrap();
+                    }
+                })
+            })
+            .collect();
+
+        let consumers: Vec<_> = (0..3)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    let mut seen = Vec::new();
+                    while let Ok(value) = queue.take() {
+                        assert!(queue.len() <= queue.capacity());
+                        seen.push(value);
+                    }
+                    seen
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        queue.close();
+
+        let mut all: Vec<_> = consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect();
+        all.sort_unstable();
+        assert_eq!(all, (0..PRODUCERS * PER_PRODUCER).collect::<Vec<_>>());
+    }
+}
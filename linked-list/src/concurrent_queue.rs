@@ -0,0 +1,448 @@
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+// Michael & Scott, "Simple, Fast, and Practical Non-Blocking and Blocking Concurrent Queue Algorithms"
+// Memory is reclaimed with hazard pointers (Michael, 2004): a thread publishes the nodes it is
+// about to dereference, and retired nodes are only freed once no hazard record points at them.
+pub struct ConcurrentQueue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    hazards: AtomicPtr<HazardRecord<T>>,
+    retired: AtomicPtr<Node<T>>,
+    retired_len: AtomicUsize,
+    _boo: PhantomData<T>,
+}
+
+struct Node<T> {
+    value: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
+    next_retired: *mut Node<T>,
+}
+
+struct HazardRecord<T> {
+    active: AtomicBool,
+    protected: [AtomicPtr<Node<T>>; 2],
+    next: *mut HazardRecord<T>,
+}
+
+struct HazardGuard<'a, T> {
+    record: &'a HazardRecord<T>,
+}
+
+const RETIRE_THRESHOLD: usize = 64;
+
+unsafe impl<T: Send> Send for ConcurrentQueue<T> {}
+unsafe impl<T: Send> Sync for ConcurrentQueue<T> {}
+
+impl<T> ConcurrentQueue<T> {
+    pub fn new() -> Self {
+        let dummy = Node::alloc(MaybeUninit::uninit());
+        ConcurrentQueue {
+            head: AtomicPtr::new(dummy),
+            tail: AtomicPtr::new(dummy),
+            hazards: AtomicPtr::new(null_mut()),
+            retired: AtomicPtr::new(null_mut()),
+            retired_len: AtomicUsize::new(0),
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn push(&self, value: T) {
+        let node = Node::alloc(MaybeUninit::new(value));
+        let guard = self.guard();
+
+        loop {
+            let tail = guard.protect(0, &self.tail);
+            let next = unsafe { (*tail).next.load(Ordering::SeqCst) };
+
+            if !next.is_null() {
+                // Somebody linked a node but has not swung the tail yet, help them
+                let _ = self
+                    .tail
+                    .compare_exchange(tail, next, Ordering::SeqCst, Ordering::SeqCst);
+                continue;
+            }
+
+            let linked = unsafe {
+                (*tail)
+                    .next
+                    .compare_exchange(null_mut(), node, Ordering::SeqCst, Ordering::SeqCst)
+            };
+            if linked.is_ok() {
+                let _ = self
+                    .tail
+                    .compare_exchange(tail, node, Ordering::SeqCst, Ordering::SeqCst);
+                return;
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let guard = self.guard();
+
+        loop {
+            let head = guard.protect(0, &self.head);
+            let next = guard.protect(1, unsafe { &(*head).next });
+
+            // `next` is only safe to use while `head` is still reachable
+            if self.head.load(Ordering::SeqCst) != head {
+                continue;
+            }
+            if next.is_null() {
+                return None;
+            }
+
+            let tail = self.tail.load(Ordering::SeqCst);
+            if head == tail {
+                let _ = self
+                    .tail
+                    .compare_exchange(tail, next, Ordering::SeqCst, Ordering::SeqCst);
+                continue;
+            }
+
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+            {
+                // `next` is the new dummy, only the winner of the CAS moves its value out
+                let value = unsafe { (*next).value.as_ptr().read() };
+                drop(guard);
+                unsafe { self.retire(head) };
+                return Some(value);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let guard = self.guard();
+        loop {
+            let head = guard.protect(0, &self.head);
+            let next = unsafe { (*head).next.load(Ordering::SeqCst) };
+            if self.head.load(Ordering::SeqCst) == head {
+                return next.is_null();
+            }
+        }
+    }
+
+    fn guard(&self) -> HazardGuard<'_, T> {
+        let mut cur = self.hazards.load(Ordering::Acquire);
+        while !cur.is_null() {
+            let record = unsafe { &*cur };
+            if record
+                .active
+                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                return HazardGuard { record };
+            }
+            cur = record.next;
+        }
+
+        let record = Box::into_raw(Box::new(HazardRecord {
+            active: AtomicBool::new(true),
+            protected: [AtomicPtr::new(null_mut()), AtomicPtr::new(null_mut())],
+            next: null_mut(),
+        }));
+        let mut head = self.hazards.load(Ordering::Acquire);
+        loop {
+            unsafe { (*record).next = head };
+            match self.hazards.compare_exchange_weak(
+                head,
+                record,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(new_head) => head = new_head,
+            }
+        }
+        HazardGuard {
+            record: unsafe { &*record },
+        }
+    }
+
+    unsafe fn retire(&self, node: *mut Node<T>) {
+        // Count before publishing so a concurrent scan can never take the counter below zero
+        let retired_len = self.retired_len.fetch_add(1, Ordering::Relaxed) + 1;
+        self.push_retired(node);
+        if retired_len >= RETIRE_THRESHOLD {
+            self.scan();
+        }
+    }
+
+    unsafe fn push_retired(&self, node: *mut Node<T>) {
+        let mut head = self.retired.load(Ordering::Relaxed);
+        loop {
+            (*node).next_retired = head;
+            match self.retired.compare_exchange_weak(
+                head,
+                node,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(new_head) => head = new_head,
+            }
+        }
+    }
+
+    unsafe fn scan(&self) {
+        let mut cur = self.retired.swap(null_mut(), Ordering::SeqCst);
+
+        let mut protected = Vec::new();
+        let mut record = self.hazards.load(Ordering::Acquire);
+        while !record.is_null() {
+            for slot in &(*record).protected {
+                let ptr = slot.load(Ordering::SeqCst);
+                if !ptr.is_null() {
+                    protected.push(ptr);
+                }
+            }
+            record = (*record).next;
+        }
+
+        while !cur.is_null() {
+            let node = cur;
+            cur = (*node).next_retired;
+            if protected.contains(&node) {
+                self.push_retired(node);
+            } else {
+                self.retired_len.fetch_sub(1, Ordering::Relaxed);
+                drop(Box::from_raw(node));
+            }
+        }
+    }
+}
+
+impl<T> Node<T> {
+    fn alloc(value: MaybeUninit<T>) -> *mut Self {
+        Box::into_raw(Box::new(Node {
+            value,
+            next: AtomicPtr::new(null_mut()),
+            next_retired: null_mut(),
+        }))
+    }
+}
+
+impl<T> HazardGuard<'_, T> {
+    fn protect(&self, slot: usize, src: &AtomicPtr<Node<T>>) -> *mut Node<T> {
+        let mut ptr = src.load(Ordering::SeqCst);
+        loop {
+            self.record.protected[slot].store(ptr, Ordering::SeqCst);
+            let again = src.load(Ordering::SeqCst);
+            if again == ptr {
+                return ptr;
+            }
+            ptr = again;
+        }
+    }
+}
+
+impl<T> Drop for HazardGuard<'_, T> {
+    fn drop(&mut self) {
+        for slot in &self.record.protected {
+            slot.store(null_mut(), Ordering::SeqCst);
+        }
+        self.record.active.store(false, Ordering::Release);
+    }
+}
+
+impl<T> Default for ConcurrentQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for ConcurrentQueue<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+
+        unsafe {
+            drop(Box::from_raw(*self.head.get_mut()));
+
+            let mut node = *self.retired.get_mut();
+            while !node.is_null() {
+                let next = (*node).next_retired;
+                drop(Box::from_raw(node));
+                node = next;
+            }
+
+            let mut record = *self.hazards.get_mut();
+            while !record.is_null() {
+                let next = (*record).next;
+                drop(Box::from_raw(record));
+                record = next;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ConcurrentQueue, RETIRE_THRESHOLD};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn basics() {
+        let queue = ConcurrentQueue::new();
+
+        // Check empty queue behaves right
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+
+        // Populate queue
+        queue.push(1);
+        queue.push(2);
+        queue.push(3);
+        assert!(!queue.is_empty());
+
+        // Check normal removal
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), Some(2));
+
+        // Push some more just to make sure nothing's corrupted
+        queue.push(4);
+        queue.push(5);
+
+        // Check normal removal
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.pop(), Some(4));
+
+        // Check exhaustion
+        assert_eq!(queue.pop(), Some(5));
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn reclaims_retired_nodes() {
+        let queue = ConcurrentQueue::new();
+        for i in 0..10 * RETIRE_THRESHOLD {
+            queue.push(i);
+            assert_eq!(queue.pop(), Some(i));
+        }
+        assert!(queue.retired_len.load(Ordering::Relaxed) < RETIRE_THRESHOLD);
+    }
+
+    #[test]
+    fn drops_remaining_values() {
+        struct Counted(Arc<AtomicUsize>);
+
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let queue = ConcurrentQueue::new();
+        for _ in 0..10 {
+            queue.push(Counted(Arc::clone(&drops)));
+        }
+        drop(queue.pop());
+        drop(queue.pop());
+        assert_eq!(drops.load(Ordering::SeqCst), 2);
+
+        drop(queue);
+        assert_eq!(drops.load(Ordering::SeqCst), 10);
+    }
+
+    #[test]
+    fn stress_mpmc() {
+        const PRODUCERS: usize = 4;
+        const CONSUMERS: usize = 4;
+        const PER_PRODUCER: usize = 20_000;
+
+        let queue = Arc::new(ConcurrentQueue::new());
+        let received = Arc::new(AtomicUsize::new(0));
+
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|producer| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    for seq in 0..PER_PRODUCER {
+                        queue.push((producer, seq));
+                    }
+                })
+            })
+            .collect();
+
+        let consumers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                let received = Arc::clone(&received);
+                thread::spawn(move || {
+                    let mut seen = Vec::new();
+                    while received.load(Ordering::SeqCst) < PRODUCERS * PER_PRODUCER {
+                        if let Some(item) = queue.pop() {
+                            received.fetch_add(1, Ordering::SeqCst);
+                            seen.push(item);
+                        } else {
+                            thread::yield_now();
+                        }
+                    }
+                    seen
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        let mut all = Vec::new();
+        for consumer in consumers {
+            let seen = consumer.join().unwrap();
+
+            // Items from one producer reach any single consumer in push order
+            let mut last = [None; PRODUCERS];
+            for &(producer, seq) in &seen {
+                assert!(last[producer] < Some(seq));
+                last[producer] = Some(seq);
+            }
+            all.extend(seen);
+        }
+
+        all.sort_unstable();
+        let expected: Vec<_> = (0..PRODUCERS)
+            .flat_map(|producer| (0..PER_PRODUCER).map(move |seq| (producer, seq)))
+            .collect();
+        assert_eq!(all, expected);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn stress_interleaved_push_pop() {
+        const THREADS: usize = 8;
+        const ROUNDS: usize = 10_000;
+
+        let queue = Arc::new(ConcurrentQueue::new());
+        let handles: Vec<_> = (0..THREADS)
+            .map(|thread| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    let mut popped = 0;
+                    for round in 0..ROUNDS {
+                        queue.push(thread * ROUNDS + round);
+                        if queue.pop().is_some() {
+                            popped += 1;
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let popped: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        let mut left = 0;
+        while queue.pop().is_some() {
+            left += 1;
+        }
+        assert_eq!(popped + left, THREADS * ROUNDS);
+    }
+}
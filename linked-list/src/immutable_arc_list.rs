@@ -0,0 +1,194 @@
+use std::sync::Arc;
+
+pub struct ArcList<T> {
+    head: Option<Arc<Node<T>>>,
+}
+
+struct Node<T> {
+    value: T,
+    next: Option<Arc<Node<T>>>,
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<T> ArcList<T> {
+    pub fn new() -> Self {
+        ArcList { head: None }
+    }
+}
+
+impl<T> ArcList<T> {
+    pub fn prepend(&self, value: T) -> Self {
+        let node = Node {
+            value,
+            next: self.head.clone(),
+        };
+        ArcList {
+            head: Some(Arc::new(node)),
+        }
+    }
+
+    pub fn drop_last(&self) -> Self {
+        ArcList {
+            head: self.head.as_ref().and_then(|node| node.next.clone()),
+        }
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+impl<T> Default for ArcList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for ArcList<T> {
+    fn drop(&mut self) {
+        let mut head = self.head.take();
+        // Unlike `try_unwrap`, `into_inner` hands the node to exactly one of several handles
+        // dropped at once, so the last owner always keeps unlinking iteratively
+        while let Some(node) = head {
+            if let Some(mut node) = Arc::into_inner(node) {
+                head = node.next.take();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.value
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ArcList;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    #[test]
+    fn basics() {
+        let list = ArcList::new();
+        assert_eq!(list.first(), None);
+
+        let list = list.prepend(1).prepend(2).prepend(3);
+        assert_eq!(list.first(), Some(&3));
+
+        let list = list.drop_last();
+        assert_eq!(list.first(), Some(&2));
+
+        let list = list.drop_last();
+        assert_eq!(list.first(), Some(&1));
+
+        let list = list.drop_last();
+        assert_eq!(list.first(), None);
+
+        // Make sure empty tail works
+        let list = list.drop_last();
+        assert_eq!(list.first(), None);
+    }
+
+    #[test]
+    fn iter() {
+        let list = ArcList::new().prepend(1).prepend(2).prepend(3);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn shared_tail_across_threads() {
+        let tail = Arc::new(ArcList::new().prepend(1).prepend(2));
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let tail = Arc::clone(&tail);
+                thread::spawn(move || {
+                    let list = tail.prepend(10 + i).prepend(20 + i);
+                    assert_eq!(list.drop_last().drop_last().first(), Some(&2));
+                    list.iter().copied().collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            let i = i as i32;
+            assert_eq!(handle.join().unwrap(), [20 + i, 10 + i, 2, 1]);
+        }
+        assert_eq!(tail.iter().copied().collect::<Vec<_>>(), [2, 1]);
+    }
+
+    #[test]
+    fn versions_move_between_threads() {
+        let base = ArcList::new().prepend(String::from("base"));
+        let derived = base.prepend(String::from("derived"));
+
+        let handle = thread::spawn(move || {
+            let grown = derived.prepend(String::from("grown"));
+            grown.iter().cloned().collect::<Vec<_>>()
+        });
+
+        assert_eq!(handle.join().unwrap(), ["grown", "derived", "base"]);
+        assert_eq!(base.first().map(String::as_str), Some("base"));
+    }
+
+    #[test]
+    fn long_list_drop() {
+        let mut list = ArcList::new();
+        for i in 0..100_000 {
+            list = list.prepend(i);
+        }
+        let shared = list.drop_last();
+        drop(list);
+        assert_eq!(shared.first(), Some(&99_998));
+    }
+
+    #[test]
+    fn long_list_dropped_from_two_threads() {
+        for _ in 0..4 {
+            let mut list = ArcList::new();
+            for i in 0..100_000 {
+                list = list.prepend(i);
+            }
+            // A second handle to the same head node
+            let twin = list.prepend(-1).drop_last();
+            let barrier = Arc::new(Barrier::new(2));
+
+            let handles: Vec<_> = [twin, list]
+                .into_iter()
+                .map(|list| {
+                    let barrier = Arc::clone(&barrier);
+                    thread::spawn(move || {
+                        barrier.wait();
+                        drop(list);
+                    })
+                })
+                .collect();
+            for handle in handles {
+                handle.join().unwrap();
+            }
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
 pub struct ImmutableList<T> {
@@ -6,10 +9,12 @@ pub struct ImmutableList<T> {
 
 struct Node<T> {
     value: T,
+    // Length of the list starting at this node, so sharing a tail shares its length too
+    len: usize,
     next: Option<Rc<Node<T>>>,
 }
 
-struct Iter<'a, T> {
+pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
 }
 
@@ -23,6 +28,7 @@ impl<T> ImmutableList<T> {
     pub fn prepend(&self, value: T) -> Self {
         let node = Node {
             value,
+            len: self.len() + 1,
             next: self.head.clone(),
         };
         ImmutableList {
@@ -30,21 +36,155 @@ impl<T> ImmutableList<T> {
         }
     }
 
+    /// Despite the name this drops the first element; kept as an alias of `tail`.
     pub fn drop_last(&self) -> Self {
+        self.tail()
+    }
+
+    /// Everything after the first element, shared with `self`. Empty if `self` is.
+    pub fn tail(&self) -> Self {
         ImmutableList {
             head: self.head.as_ref().and_then(|node| node.next.clone()),
         }
     }
 
+    pub fn uncons(&self) -> Option<(&T, Self)> {
+        self.head.as_ref().map(|node| {
+            let tail = ImmutableList {
+                head: node.next.clone(),
+            };
+            (&node.value, tail)
+        })
+    }
+
     pub fn first(&self) -> Option<&T> {
         self.head.as_ref().map(|node| &node.value)
     }
 
+    pub fn len(&self) -> usize {
+        self.head.as_ref().map_or(0, |node| node.len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn nth(&self, n: usize) -> Option<&T> {
+        self.iter().nth(n)
+    }
+
+    /// The list without its first `n` elements, shared with `self`.
+    pub fn skip(&self, n: usize) -> Self {
+        let mut head = self.head.as_ref();
+        for _ in 0..n.min(self.len()) {
+            head = head.and_then(|node| node.next.as_ref());
+        }
+        ImmutableList {
+            head: head.cloned(),
+        }
+    }
+
+    /// The first `n` elements. They are copied unless `n` covers the whole list, in which case
+    /// the result shares everything with `self`.
+    pub fn take(&self, n: usize) -> Self
+    where
+        T: Clone,
+    {
+        if n >= self.len() {
+            return self.clone();
+        }
+        Self::copy_onto(self.iter().take(n), &ImmutableList::new())
+    }
+
+    /// Copies the elements of `self` in front of `other`, which is shared rather than copied.
+    pub fn append(&self, other: &Self) -> Self
+    where
+        T: Clone,
+    {
+        if other.is_empty() {
+            return self.clone();
+        }
+        Self::copy_onto(self.iter(), other)
+    }
+
+    /// Joins the lists in order. Only the last one is shared, the rest are copied.
+    pub fn concat<'a>(lists: impl IntoIterator<Item = &'a Self>) -> Self
+    where
+        T: Clone + 'a,
+    {
+        let lists: Vec<_> = lists.into_iter().collect();
+        lists
+            .into_iter()
+            .rev()
+            .fold(ImmutableList::new(), |rest, list| list.append(&rest))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> ImmutableList<U> {
+        self.iter().map(f).collect()
+    }
+
+    pub fn fold<B>(&self, init: B, f: impl FnMut(B, &T) -> B) -> B {
+        self.iter().fold(init, f)
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|item| item == value)
+    }
+
+    fn copy_onto<'a>(values: impl Iterator<Item = &'a T>, rest: &Self) -> Self
+    where
+        T: Clone + 'a,
+    {
+        let values: Vec<_> = values.collect();
+        values
+            .into_iter()
+            .rev()
+            .fold(rest.clone(), |list, value| list.prepend(value.clone()))
+    }
+
     pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             next: self.head.as_deref(),
         }
     }
+
+    /// Returns a reversed copy. Every node has to be rebuilt, so nothing is shared with `self`.
+    pub fn reversed(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.iter().fold(ImmutableList::new(), |list, value| {
+            list.prepend(value.clone())
+        })
+    }
+}
+
+/// O(1): the clone shares every node with `self`.
+impl<T> Clone for ImmutableList<T> {
+    fn clone(&self) -> Self {
+        ImmutableList {
+            head: self.head.clone(),
+        }
+    }
+}
+
+impl<T> Default for ImmutableList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<T> for ImmutableList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values: Vec<_> = iter.into_iter().collect();
+        values
+            .into_iter()
+            .rev()
+            .fold(ImmutableList::new(), |list, value| list.prepend(value))
+    }
 }
 
 impl<T> Drop for ImmutableList<T> {
@@ -60,6 +200,15 @@ impl<T> Drop for ImmutableList<T> {
     }
 }
 
+impl<'a, T> IntoIterator for &'a ImmutableList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl<'a, T> Iterator for Iter<'a, T> {
     type Item = &'a T;
 
@@ -69,11 +218,96 @@ impl<'a, T> Iterator for Iter<'a, T> {
             &node.value
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {
+    fn len(&self) -> usize {
+        self.next.map_or(0, |node| node.len)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ImmutableList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+/// Stops comparing as soon as both lists reach the same node, since everything from there on is
+/// shared. That makes comparing a list with itself, or with a version built on it, cheap.
+///
+/// A shared node counts as equal by identity, so a list holding `f64::NAN` equals itself even
+/// though `NAN != NAN`. `partial_cmp` takes the same shortcut and stays consistent with this.
+impl<T: PartialEq> PartialEq for ImmutableList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        let (mut left, mut right) = (self.head.as_ref(), other.head.as_ref());
+        while let (Some(l), Some(r)) = (left, right) {
+            if Rc::ptr_eq(l, r) {
+                return true;
+            }
+            if l.value != r.value {
+                return false;
+            }
+            left = l.next.as_ref();
+            right = r.next.as_ref();
+        }
+        true
+    }
+}
+
+impl<T: Eq> Eq for ImmutableList<T> {}
+
+/// Treats a shared node as equal to itself, like `eq`.
+impl<T: PartialOrd> PartialOrd for ImmutableList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let (mut left, mut right) = (self.head.as_ref(), other.head.as_ref());
+        loop {
+            match (left, right) {
+                (Some(l), Some(r)) => {
+                    if Rc::ptr_eq(l, r) {
+                        return Some(Ordering::Equal);
+                    }
+                    match l.value.partial_cmp(&r.value) {
+                        Some(Ordering::Equal) => {}
+                        unequal => return unequal,
+                    }
+                    left = l.next.as_ref();
+                    right = r.next.as_ref();
+                }
+                (Some(_), None) => return Some(Ordering::Greater),
+                (None, Some(_)) => return Some(Ordering::Less),
+                (None, None) => return Some(Ordering::Equal),
+            }
+        }
+    }
+}
+
+impl<T: Ord> Ord for ImmutableList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for ImmutableList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::ImmutableList;
+    use std::rc::Rc;
 
     #[test]
     fn basics() {
@@ -106,4 +340,147 @@ mod test {
         assert_eq!(iter.next(), Some(&2));
         assert_eq!(iter.next(), Some(&1));
     }
+
+    #[test]
+    fn reversed() {
+        let list = ImmutableList::new().prepend(1).prepend(2).prepend(3);
+        let reversed = list.reversed();
+
+        assert_eq!(reversed.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [3, 2, 1]);
+        assert_eq!(ImmutableList::<i32>::new().reversed().first(), None);
+    }
+
+    fn shares<T>(a: &ImmutableList<T>, b: &ImmutableList<T>) -> bool {
+        match (&a.head, &b.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    fn to_vec<T: Copy>(list: &ImmutableList<T>) -> Vec<T> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn len_tail_uncons() {
+        let list: ImmutableList<_> = (1..=4).collect();
+        assert_eq!(to_vec(&list), [1, 2, 3, 4]);
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.prepend(0).len(), 5);
+
+        let (first, tail) = list.uncons().unwrap();
+        assert_eq!(*first, 1);
+        assert_eq!(tail.len(), 3);
+        assert!(shares(&tail, &list.tail()));
+        assert!(ImmutableList::<i32>::new().uncons().is_none());
+        assert!(ImmutableList::<i32>::new().tail().is_empty());
+
+        assert_eq!(list.nth(0), Some(&1));
+        assert_eq!(list.nth(3), Some(&4));
+        assert_eq!(list.nth(4), None);
+        assert!(list.contains(&3));
+        assert!(!list.contains(&5));
+    }
+
+    #[test]
+    fn take_and_skip() {
+        let list: ImmutableList<_> = (0..5).collect();
+
+        let skipped = list.skip(2);
+        assert_eq!(to_vec(&skipped), [2, 3, 4]);
+        assert_eq!(skipped.len(), 3);
+        assert!(shares(&skipped, &list.tail().tail()));
+        assert!(list.skip(9).is_empty());
+        assert!(list.skip(usize::MAX).is_empty());
+
+        let taken = list.take(2);
+        assert_eq!(to_vec(&taken), [0, 1]);
+        assert_eq!(taken.len(), 2);
+        assert!(list.take(0).is_empty());
+        assert!(shares(&list.take(5), &list));
+        assert!(shares(&list.take(usize::MAX), &list));
+        assert_eq!(to_vec(&list), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn append_and_concat() {
+        let left: ImmutableList<_> = (0..3).collect();
+        let right: ImmutableList<_> = (3..5).collect();
+
+        let joined = left.append(&right);
+        assert_eq!(to_vec(&joined), [0, 1, 2, 3, 4]);
+        assert_eq!(joined.len(), 5);
+        assert!(shares(&joined.skip(3), &right));
+        assert_eq!(to_vec(&left), [0, 1, 2]);
+
+        assert!(shares(&ImmutableList::new().append(&right), &right));
+        assert!(shares(&left.append(&ImmutableList::new()), &left));
+
+        let empty = ImmutableList::new();
+        let all = ImmutableList::concat([&left, &empty, &right, &left]);
+        assert_eq!(to_vec(&all), [0, 1, 2, 3, 4, 0, 1, 2]);
+        assert!(shares(&all.skip(5), &left));
+        assert!(ImmutableList::<u8>::concat([]).is_empty());
+    }
+
+    #[test]
+    fn map_and_fold() {
+        let list: ImmutableList<_> = (1..=4).collect();
+        let strings = list.map(|value| value.to_string());
+        assert_eq!(strings.iter().cloned().collect::<String>(), "1234");
+        assert_eq!(strings.len(), 4);
+        assert_eq!(list.fold(0, |sum, value| sum + value), 10);
+    }
+
+    #[test]
+    fn traits() {
+        let list: ImmutableList<_> = (1..=3).collect();
+        let copy = list.clone();
+        assert!(shares(&list, &copy));
+        assert_eq!(format!("{:?}", copy), "[1, 2, 3]");
+
+        // Equal by value, by shared nodes, or by a shared tail
+        assert_eq!(list, copy);
+        assert_eq!(list, (1..=3).collect());
+        assert_eq!(list.prepend(0), copy.prepend(0));
+        assert_ne!(list, list.prepend(0).tail().prepend(9));
+        assert_ne!(list, list.tail());
+        assert_eq!(ImmutableList::<u8>::new(), ImmutableList::default());
+
+        assert!(list.tail() > list);
+        assert!(list < list.prepend(2));
+        assert_eq!(list.cmp(&copy), std::cmp::Ordering::Equal);
+
+        // Shared nodes are equal by identity, and ordering agrees with equality
+        let nan: ImmutableList<_> = [1.0, f64::NAN].into_iter().collect();
+        let fresh: ImmutableList<_> = [1.0, f64::NAN].into_iter().collect();
+        assert_eq!(nan, nan.clone());
+        assert_eq!(
+            nan.partial_cmp(&nan.clone()),
+            Some(std::cmp::Ordering::Equal)
+        );
+        assert_ne!(nan, fresh);
+        assert_eq!(nan.partial_cmp(&fresh), None);
+        assert!(nan.prepend(0.0) < fresh);
+
+        let mut map = std::collections::HashMap::new();
+        map.insert(list.clone(), "list");
+        assert_eq!(
+            map.get(&(1..=3).collect::<ImmutableList<_>>()),
+            Some(&"list")
+        );
+        assert_eq!(map.get(&list.tail()), None);
+
+        let mut total = 0;
+        for value in &list {
+            total += value;
+        }
+        assert_eq!(total, 6);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 3);
+        iter.next();
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+    }
 }
@@ -0,0 +1,570 @@
+use std::cell::Cell;
+use std::fmt;
+use std::marker::{PhantomData, PhantomPinned};
+use std::pin::Pin;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Picks the `Links` field of `Target` that a list threads through. An object that embeds several
+/// `Links` fields can sit in one list per field, each list using its own adapter.
+///
+/// # Safety
+///
+/// `links` must always return the same field of `target`, and two adapters used on the same
+/// object must never return the same field.
+pub unsafe trait Adapter {
+    type Target;
+
+    fn links(target: &Self::Target) -> &Links<Self::Target>;
+}
+
+/// Link field embedded in caller-owned nodes. It makes the owner `!Unpin`, so nodes have to be
+/// pinned before they can be linked.
+pub struct Links<T> {
+    prev: Cell<Link<T>>,
+    next: Cell<Link<T>>,
+    owner: Cell<usize>,
+    _pin: PhantomPinned,
+}
+
+/**
+run tests:
+MIRIFLAGS="-Zmiri-tag-raw-pointers" cargo  +nightly-2023-06-18 miri test
+ */
+pub struct IntrusiveList<'a, A: Adapter> {
+    first: Link<A::Target>,
+    last: Link<A::Target>,
+    len: usize,
+    id: usize,
+    _boo: PhantomData<&'a A::Target>,
+}
+
+pub struct Iter<'l, 'a, A: Adapter> {
+    first: Link<A::Target>,
+    last: Link<A::Target>,
+    len: usize,
+    _boo: PhantomData<&'l IntrusiveList<'a, A>>,
+}
+
+pub struct CursorMut<'l, 'a, A: Adapter> {
+    cur: Link<A::Target>,
+    list: &'l mut IntrusiveList<'a, A>,
+    index: Option<usize>,
+}
+
+type Link<T> = Option<NonNull<T>>;
+
+// Tags each list so a node can tell which one it is linked into; 0 means unlinked
+static NEXT_LIST_ID: AtomicUsize = AtomicUsize::new(1);
+
+impl<T> Links<T> {
+    pub const fn new() -> Self {
+        Links {
+            prev: Cell::new(None),
+            next: Cell::new(None),
+            owner: Cell::new(0),
+            _pin: PhantomPinned,
+        }
+    }
+
+    pub fn is_linked(&self) -> bool {
+        self.owner.get() != 0
+    }
+}
+
+impl<T> Default for Links<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for Links<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Links")
+            .field("linked", &self.is_linked())
+            .finish()
+    }
+}
+
+impl<'a, A: Adapter> IntrusiveList<'a, A> {
+    pub fn new() -> Self {
+        IntrusiveList {
+            first: None,
+            last: None,
+            len: 0,
+            id: NEXT_LIST_ID.fetch_add(1, Ordering::Relaxed),
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, 'a, A> {
+        CursorMut {
+            cur: None,
+            list: self,
+            index: None,
+        }
+    }
+
+    /// Panics if `node` is already linked through this adapter's field.
+    pub fn push(&mut self, node: Pin<&'a A::Target>) {
+        self.link_between(node, None, self.first);
+    }
+
+    /// Panics if `node` is already linked through this adapter's field.
+    pub fn push_back(&mut self, node: Pin<&'a A::Target>) {
+        self.link_between(node, self.last, None);
+    }
+
+    pub fn pop_first(&mut self) -> Option<Pin<&'a A::Target>> {
+        let node = self.first?;
+        self.unlink(node);
+        Some(Self::pin(node))
+    }
+
+    pub fn pop_back(&mut self) -> Option<Pin<&'a A::Target>> {
+        let node = self.last?;
+        self.unlink(node);
+        Some(Self::pin(node))
+    }
+
+    pub fn first(&self) -> Option<Pin<&'a A::Target>> {
+        self.first.map(Self::pin)
+    }
+
+    pub fn back(&self) -> Option<Pin<&'a A::Target>> {
+        self.last.map(Self::pin)
+    }
+
+    pub fn contains(&self, node: &A::Target) -> bool {
+        A::links(node).owner.get() == self.id
+    }
+
+    /// Unlinks `node` in O(1). Returns `false` if it is not in this list.
+    pub fn remove(&mut self, node: &A::Target) -> bool {
+        if !self.contains(node) {
+            return false;
+        }
+        self.unlink(NonNull::from(node));
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_first().is_some() {}
+    }
+
+    pub fn iter(&self) -> Iter<'_, 'a, A> {
+        Iter {
+            first: self.first,
+            last: self.last,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+
+    fn link_between(
+        &mut self,
+        node: Pin<&'a A::Target>,
+        prev: Link<A::Target>,
+        next: Link<A::Target>,
+    ) {
+        let links = A::links(node.get_ref());
+        assert!(!links.is_linked(), "node is already linked");
+
+        let new = NonNull::from(node.get_ref());
+        links.owner.set(self.id);
+        links.prev.set(prev);
+        links.next.set(next);
+
+        match prev {
+            Some(prev) => Self::links(prev).next.set(Some(new)),
+            None => self.first = Some(new),
+        }
+        match next {
+            Some(next) => Self::links(next).prev.set(Some(new)),
+            None => self.last = Some(new),
+        }
+        self.len += 1;
+    }
+
+    fn unlink(&mut self, node: NonNull<A::Target>) {
+        let links = Self::links(node);
+        let prev = links.prev.take();
+        let next = links.next.take();
+        links.owner.set(0);
+
+        match prev {
+            Some(prev) => Self::links(prev).next.set(next),
+            None => self.first = next,
+        }
+        match next {
+            Some(next) => Self::links(next).prev.set(prev),
+            None => self.last = prev,
+        }
+        self.len -= 1;
+    }
+
+    fn links(node: NonNull<A::Target>) -> &'a Links<A::Target> {
+        // Every linked node is borrowed for 'a, so it stays alive and in place
+        unsafe { A::links(&*node.as_ptr()) }
+    }
+
+    fn pin(node: NonNull<A::Target>) -> Pin<&'a A::Target> {
+        unsafe { Pin::new_unchecked(&*node.as_ptr()) }
+    }
+}
+
+impl<A: Adapter> Default for IntrusiveList<'_, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Adapter> Drop for IntrusiveList<'_, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<A: Adapter> fmt::Debug for IntrusiveList<'_, A>
+where
+    A::Target: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'l, 'a, A: Adapter> IntoIterator for &'l IntrusiveList<'a, A> {
+    type Item = Pin<&'a A::Target>;
+    type IntoIter = Iter<'l, 'a, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, A: Adapter> Iterator for Iter<'_, 'a, A> {
+    type Item = Pin<&'a A::Target>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.first?;
+        self.len -= 1;
+        self.first = IntrusiveList::<A>::links(node).next.get();
+        Some(IntrusiveList::<A>::pin(node))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<A: Adapter> DoubleEndedIterator for Iter<'_, '_, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.last?;
+        self.len -= 1;
+        self.last = IntrusiveList::<A>::links(node).prev.get();
+        Some(IntrusiveList::<A>::pin(node))
+    }
+}
+
+impl<A: Adapter> ExactSizeIterator for Iter<'_, '_, A> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, A: Adapter> CursorMut<'_, 'a, A> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = IntrusiveList::<A>::links(cur).next.get();
+            if self.cur.is_some() {
+                *self.index.as_mut().unwrap() += 1;
+            } else {
+                self.index = None;
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.first;
+            self.index = Some(0);
+        }
+    }
+
+    pub fn move_back(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = IntrusiveList::<A>::links(cur).prev.get();
+            if self.cur.is_some() {
+                *self.index.as_mut().unwrap() -= 1;
+            } else {
+                self.index = None;
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.last;
+            self.index = Some(self.list.len - 1);
+        }
+    }
+
+    pub fn current(&self) -> Option<Pin<&'a A::Target>> {
+        self.cur.map(IntrusiveList::<A>::pin)
+    }
+
+    pub fn peek_next(&self) -> Option<Pin<&'a A::Target>> {
+        let next = match self.cur {
+            Some(cur) => IntrusiveList::<A>::links(cur).next.get(),
+            None => self.list.first,
+        };
+        next.map(IntrusiveList::<A>::pin)
+    }
+
+    pub fn peek_back(&self) -> Option<Pin<&'a A::Target>> {
+        let prev = match self.cur {
+            Some(cur) => IntrusiveList::<A>::links(cur).prev.get(),
+            None => self.list.last,
+        };
+        prev.map(IntrusiveList::<A>::pin)
+    }
+
+    pub fn insert_before(&mut self, node: Pin<&'a A::Target>) {
+        match self.cur {
+            Some(cur) => {
+                let prev = IntrusiveList::<A>::links(cur).prev.get();
+                self.list.link_between(node, prev, Some(cur));
+                *self.index.as_mut().unwrap() += 1;
+            }
+            None => self.list.push_back(node),
+        }
+    }
+
+    pub fn insert_after(&mut self, node: Pin<&'a A::Target>) {
+        match self.cur {
+            Some(cur) => {
+                let next = IntrusiveList::<A>::links(cur).next.get();
+                self.list.link_between(node, Some(cur), next);
+            }
+            None => self.list.push(node),
+        }
+    }
+
+    pub fn remove_current(&mut self) -> Option<Pin<&'a A::Target>> {
+        let cur = self.cur?;
+        let next = IntrusiveList::<A>::links(cur).next.get();
+        self.list.unlink(cur);
+
+        self.cur = next;
+        if next.is_none() {
+            self.index = None;
+        }
+        Some(IntrusiveList::<A>::pin(cur))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Adapter, IntrusiveList, Links};
+    use std::pin::{pin, Pin};
+
+    #[derive(Default)]
+    struct Task {
+        id: u32,
+        run_links: Links<Task>,
+        all_links: Links<Task>,
+    }
+
+    impl Task {
+        fn new(id: u32) -> Self {
+            Task {
+                id,
+                ..Task::default()
+            }
+        }
+    }
+
+    struct RunQueue;
+
+    unsafe impl Adapter for RunQueue {
+        type Target = Task;
+
+        fn links(target: &Task) -> &Links<Task> {
+            &target.run_links
+        }
+    }
+
+    struct AllTasks;
+
+    unsafe impl Adapter for AllTasks {
+        type Target = Task;
+
+        fn links(target: &Task) -> &Links<Task> {
+            &target.all_links
+        }
+    }
+
+    fn ids<A: Adapter<Target = Task>>(list: &IntrusiveList<'_, A>) -> Vec<u32> {
+        let forward: Vec<_> = list.iter().map(|task| task.id).collect();
+        let mut backward: Vec<_> = list.iter().rev().map(|task| task.id).collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+        assert_eq!(forward.len(), list.len());
+        forward
+    }
+
+    #[test]
+    fn basics() {
+        let a = pin!(Task::new(1));
+        let b = pin!(Task::new(2));
+        let c = pin!(Task::new(3));
+        let (a, b, c) = (a.into_ref(), b.into_ref(), c.into_ref());
+
+        let mut list = IntrusiveList::<RunQueue>::new();
+        assert!(list.is_empty());
+        assert!(list.pop_first().is_none());
+
+        list.push(b);
+        list.push(a);
+        list.push_back(c);
+        assert_eq!(ids(&list), [1, 2, 3]);
+        assert_eq!(list.first().map(|task| task.id), Some(1));
+        assert_eq!(list.back().map(|task| task.id), Some(3));
+
+        assert_eq!(list.pop_first().map(|task| task.id), Some(1));
+        assert_eq!(list.pop_back().map(|task| task.id), Some(3));
+        assert!(!a.run_links.is_linked());
+        assert!(b.run_links.is_linked());
+
+        // Popped nodes can be linked again
+        list.push_back(a);
+        assert_eq!(ids(&list), [2, 1]);
+    }
+
+    #[test]
+    fn remove_known_node() {
+        let boxed: Vec<_> = (0..4).map(|id| Box::pin(Task::new(id))).collect();
+        let tasks: Vec<Pin<&Task>> = boxed.iter().map(|task| task.as_ref()).collect();
+
+        let mut list = IntrusiveList::<RunQueue>::new();
+        let mut other = IntrusiveList::<RunQueue>::new();
+        for &task in &tasks[..3] {
+            list.push_back(task);
+        }
+        other.push_back(tasks[3]);
+
+        assert!(list.remove(&tasks[1]));
+        assert!(!list.remove(&tasks[1]));
+        assert!(!list.remove(&tasks[3]));
+        assert_eq!(ids(&list), [0, 2]);
+        assert_eq!(ids(&other), [3]);
+
+        assert!(list.remove(&tasks[0]));
+        assert!(list.remove(&tasks[2]));
+        assert!(list.is_empty());
+        assert!(list.first().is_none() && list.back().is_none());
+    }
+
+    #[test]
+    fn several_lists_at_once() {
+        let a = pin!(Task::new(1));
+        let b = pin!(Task::new(2));
+        let c = pin!(Task::new(3));
+        let (a, b, c) = (a.into_ref(), b.into_ref(), c.into_ref());
+
+        let mut all = IntrusiveList::<AllTasks>::new();
+        let mut runnable = IntrusiveList::<RunQueue>::new();
+        for task in [a, b, c] {
+            all.push_back(task);
+        }
+        runnable.push_back(c);
+        runnable.push_back(a);
+
+        assert!(runnable.remove(&c));
+        assert_eq!(ids(&runnable), [1]);
+        assert_eq!(ids(&all), [1, 2, 3]);
+
+        assert!(all.remove(&a));
+        assert_eq!(ids(&all), [2, 3]);
+        assert!(runnable.contains(&a));
+        assert!(!all.contains(&a));
+    }
+
+    #[test]
+    #[should_panic(expected = "node is already linked")]
+    fn double_link_panics() {
+        let a = pin!(Task::new(1));
+        let a = a.into_ref();
+
+        let mut first = IntrusiveList::<RunQueue>::new();
+        let mut second = IntrusiveList::<RunQueue>::new();
+        first.push(a);
+        second.push(a);
+    }
+
+    #[test]
+    fn drop_unlinks_nodes() {
+        let a = pin!(Task::new(1));
+        let a = a.into_ref();
+        {
+            let mut list = IntrusiveList::<RunQueue>::new();
+            list.push(a);
+        }
+        assert!(!a.run_links.is_linked());
+
+        let mut list = IntrusiveList::<RunQueue>::new();
+        list.push(a);
+        assert_eq!(ids(&list), [1]);
+    }
+
+    #[test]
+    fn cursor_mut() {
+        let boxed: Vec<_> = (0..5).map(|id| Box::pin(Task::new(id))).collect();
+        let tasks: Vec<Pin<&Task>> = boxed.iter().map(|task| task.as_ref()).collect();
+
+        let mut list = IntrusiveList::<RunQueue>::new();
+        list.push_back(tasks[1]);
+        list.push_back(tasks[3]);
+
+        let mut cursor = list.cursor_mut();
+        assert!(cursor.current().is_none());
+        assert_eq!(cursor.peek_next().map(|task| task.id), Some(1));
+        assert_eq!(cursor.peek_back().map(|task| task.id), Some(3));
+
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(0));
+        cursor.insert_before(tasks[0]);
+        cursor.insert_after(tasks[2]);
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.current().map(|task| task.id), Some(1));
+
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current().map(|task| task.id), Some(3));
+        assert!(cursor.current().is_none());
+        assert_eq!(cursor.index(), None);
+
+        // At the ghost, inserting before appends and inserting after prepends
+        cursor.insert_before(tasks[4]);
+        cursor.move_back();
+        assert_eq!(cursor.current().map(|task| task.id), Some(4));
+        assert_eq!(cursor.index(), Some(3));
+        cursor.move_back();
+        assert_eq!(cursor.remove_current().map(|task| task.id), Some(2));
+        assert_eq!(cursor.current().map(|task| task.id), Some(4));
+        assert_eq!(cursor.index(), Some(2));
+
+        assert_eq!(ids(&list), [0, 1, 4]);
+    }
+}
@@ -0,0 +1,20 @@
+pub mod allocator;
+pub mod arena_list;
+pub mod async_channel;
+pub mod blocking_queue;
+pub mod concurrent_queue;
+pub mod immutable_arc_list;
+pub mod immutable_linked_list;
+pub mod intrusive_list;
+pub mod linked_list;
+pub mod list_zipper;
+pub mod lru_cache;
+pub mod persistent_deque;
+pub mod rng;
+pub mod safe_deque;
+pub mod skip_list;
+pub mod stack_list;
+pub mod traits;
+pub mod unrolled_list;
+pub mod unsafe_linked_list;
+pub mod unsafe_queue;
@@ -1,6 +1,11 @@
 // https://rust-unofficial.github.io/too-many-lists/index.html
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
 pub struct LinkedList<T> {
     head: Link<T>,
+    len: usize,
 }
 
 pub struct IntoIter<T>(LinkedList<T>);
@@ -22,7 +27,7 @@ type Link<T> = Option<Box<LinkedNode<T>>>;
 
 impl<T> LinkedList<T> {
     pub fn new() -> Self {
-        LinkedList { head: None }
+        LinkedList { head: None, len: 0 }
     }
 
     pub fn push(&mut self, value: T) {
@@ -30,12 +35,14 @@ impl<T> LinkedList<T> {
             value,
             next: self.head.take(),
         };
-        self.head = Some(Box::new(node))
+        self.head = Some(Box::new(node));
+        self.len += 1;
     }
 
     pub fn pop(&mut self) -> Option<T> {
         self.head.take().map(|node| {
             self.head = node.next;
+            self.len -= 1;
             node.value
         })
     }
@@ -47,23 +54,191 @@ impl<T> LinkedList<T> {
     pub fn peek_mut(&mut self) -> Option<&mut T> {
         self.head.as_mut().map(|node| &mut node.value)
     }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Reverses the list in place by relinking its nodes.
+    pub fn reverse(&mut self) {
+        let mut reversed = None;
+        let mut cur = self.head.take();
+        while let Some(mut node) = cur {
+            cur = node.next.take();
+            node.next = reversed;
+            reversed = Some(node);
+        }
+        self.head = reversed;
+    }
+}
+
+impl<T> LinkedList<T> {
+    /// Stable merge sort that relinks the existing nodes, O(n log n) and without allocating.
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn sort_by_key<K: Ord>(&mut self, mut f: impl FnMut(&T) -> K) {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// If `compare` panics the list keeps all of its elements, in an unspecified order.
+    pub fn sort_by(&mut self, compare: impl FnMut(&T, &T) -> Ordering) {
+        let rest = self.head.take();
+        MergeSort {
+            list: self,
+            runs: [const { None }; usize::BITS as usize],
+            rest,
+            left: None,
+            right: None,
+            merged: None,
+        }
+        .run(compare);
+    }
+
+    pub fn is_sorted(&self) -> bool
+    where
+        T: PartialOrd,
+    {
+        self.iter().zip(self.iter().skip(1)).all(|(a, b)| a <= b)
+    }
+}
+
+/// Bottom-up merge sort. Every node is always owned by one of the fields, and dropping the
+/// sorter, even while unwinding from a panicking comparison, hands them all back to the list.
+struct MergeSort<'a, T> {
+    list: &'a mut LinkedList<T>,
+    // `runs[i]` is empty or holds 2^i sorted nodes that came before every lower run
+    runs: [Link<T>; usize::BITS as usize],
+    rest: Link<T>,
+    left: Link<T>,
+    right: Link<T>,
+    merged: Link<T>,
+}
+
+impl<T> MergeSort<'_, T> {
+    fn run(mut self, mut compare: impl FnMut(&T, &T) -> Ordering) {
+        while let Some(mut node) = self.rest.take() {
+            self.rest = node.next.take();
+            let mut carry = Some(node);
+            for level in 0.. {
+                match self.runs[level].take() {
+                    Some(run) => {
+                        self.left = Some(run);
+                        self.right = carry;
+                        carry = self.merge(&mut compare);
+                    }
+                    None => {
+                        self.runs[level] = carry;
+                        break;
+                    }
+                }
+            }
+        }
+
+        for level in 0..self.runs.len() {
+            if let Some(run) = self.runs[level].take() {
+                self.left = Some(run);
+                self.right = self.merged.take();
+                self.merged = self.merge(&mut compare);
+            }
+        }
+    }
+
+    /// Merges `left` into `right`, taking from `left` on ties, and returns the result.
+    fn merge(&mut self, compare: &mut impl FnMut(&T, &T) -> Ordering) -> Link<T> {
+        let MergeSort {
+            left,
+            right,
+            merged,
+            ..
+        } = &mut *self;
+        let mut tail = merged;
+        while let (Some(l), Some(r)) = (left.as_deref(), right.as_deref()) {
+            let from = if compare(&r.value, &l.value) == Ordering::Less {
+                &mut *right
+            } else {
+                &mut *left
+            };
+            let mut node = from.take().unwrap();
+            *from = node.next.take();
+            tail = &mut tail.insert(node).next;
+        }
+        *tail = left.take().or(right.take());
+        self.merged.take()
+    }
+}
+
+impl<T> Drop for MergeSort<'_, T> {
+    fn drop(&mut self) {
+        let chains = self.runs.iter_mut().rev().chain([
+            &mut self.merged,
+            &mut self.left,
+            &mut self.right,
+            &mut self.rest,
+        ]);
+
+        let mut head = None;
+        let mut tail = &mut head;
+        for chain in chains {
+            *tail = chain.take();
+            while let Some(node) = tail {
+                tail = &mut node.next;
+            }
+        }
+        self.list.head = head;
+    }
 }
 
 impl<T> Drop for LinkedList<T> {
     fn drop(&mut self) {
         let mut cur_link = self.head.take();
-        while let Some(_) = cur_link {
-            cur_link = self.head.take();
+        while let Some(mut node) = cur_link {
+            cur_link = node.next.take();
         }
     }
 }
 
-impl<T> LinkedList<T> {
-    pub fn into_iter(self) -> IntoIter<T> {
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
         IntoIter(self)
     }
 }
 
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
 impl<T> LinkedList<T> {
     pub fn iter(&self) -> Iter<'_, T> {
         Iter {
@@ -86,6 +261,16 @@ impl<T> Iterator for IntoIter<T> {
     fn next(&mut self) -> Option<Self::Item> {
         self.0.pop()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {
+    fn len(&self) -> usize {
+        self.0.len
+    }
 }
 
 impl<'a, T> Iterator for Iter<'a, T> {
@@ -110,6 +295,71 @@ impl<'a, T> Iterator for IterMut<'a, T> {
     }
 }
 
+/// Extending appends at the bottom of the stack, so collecting keeps the iterator's order:
+/// the first item ends up on top.
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut tail = &mut self.head;
+        while let Some(node) = tail {
+            tail = &mut node.next;
+        }
+        for value in iter {
+            let node = tail.insert(Box::new(LinkedNode { value, next: None }));
+            tail = &mut node.next;
+            self.len += 1;
+        }
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Clone> Clone for LinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for LinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for LinkedList<T> {}
+
+impl<T: PartialOrd> PartialOrd for LinkedList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for LinkedList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for LinkedList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::linked_list::LinkedList;
@@ -143,6 +393,16 @@ mod test {
         assert_eq!(list.pop(), None);
     }
 
+    #[test]
+    fn long_list_drop() {
+        // Dropping recursively would overflow the stack here
+        let mut list = LinkedList::new();
+        for i in 0..100_000 {
+            list.push(i);
+        }
+        drop(list);
+    }
+
     #[test]
     fn peek() {
         let mut list = LinkedList::new();
@@ -154,12 +414,34 @@ mod test {
 
         assert_eq!(list.peek(), Some(&3));
         assert_eq!(list.peek_mut(), Some(&mut 3));
-        list.peek_mut().map(|value| *value = 42);
+        if let Some(value) = list.peek_mut() {
+            *value = 42;
+        }
 
         assert_eq!(list.peek(), Some(&42));
         assert_eq!(list.pop(), Some(42));
     }
 
+    #[test]
+    fn reverse() {
+        let mut list = LinkedList::new();
+        list.reverse();
+        assert_eq!(list.peek(), None);
+
+        for i in 0..5 {
+            list.push(i);
+        }
+        list.reverse();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+
+        list.push(-1);
+        list.reverse();
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            [4, 3, 2, 1, 0, -1]
+        );
+    }
+
     #[test]
     fn into_iter() {
         let mut list = LinkedList::new();
@@ -187,6 +469,58 @@ mod test {
         assert_eq!(iter.next(), Some(&1));
     }
 
+    #[test]
+    fn sort() {
+        let mut list = LinkedList::new();
+        list.sort();
+        assert!(list.is_sorted());
+
+        let mut rng = crate::rng::SplitMix64::new(21);
+        let pairs: Vec<_> = (0..500).map(|i| (rng.next_u64() % 9, i)).collect();
+        for &pair in pairs.iter().rev() {
+            list.push(pair);
+        }
+        assert!(!list.is_sorted());
+
+        // Equal keys keep their relative order
+        list.sort_by_key(|&(key, _)| key);
+        let mut expected = pairs.clone();
+        expected.sort_by_key(|&(key, _)| key);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), expected);
+        assert!(list.is_sorted());
+
+        list.sort_by(|a, b| b.cmp(a));
+        expected.sort_by(|a, b| b.cmp(a));
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), expected);
+
+        list.sort();
+        expected.sort();
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn sort_panic_keeps_elements() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut list = LinkedList::new();
+        for value in 0..100 {
+            list.push(value);
+        }
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            list.sort_by(|a, b| {
+                calls += 1;
+                assert!(calls < 150, "comparison failed");
+                a.cmp(b)
+            })
+        }));
+        assert!(result.is_err());
+
+        let mut values: Vec<_> = list.iter().cloned().collect();
+        values.sort();
+        assert_eq!(values, (0..100).collect::<Vec<_>>());
+    }
+
     #[test]
     fn iter_mut() {
         let mut list = LinkedList::new();
@@ -199,4 +533,59 @@ mod test {
         assert_eq!(iter.next(), Some(&mut 2));
         assert_eq!(iter.next(), Some(&mut 1));
     }
+
+    #[test]
+    fn len_and_collect() {
+        let mut list: LinkedList<_> = (1..=3).collect();
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.peek(), Some(&1));
+
+        list.push(0);
+        list.extend([4, 5]);
+        assert_eq!(list.len(), 6);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5]);
+
+        for value in &mut list {
+            *value *= 2;
+        }
+        assert_eq!(list.pop(), Some(0));
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.into_iter().len(), 5);
+
+        let mut empty = LinkedList::new();
+        assert!(empty.is_empty());
+        empty.extend(Vec::<u8>::new());
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn clone_eq_ord() {
+        let list: LinkedList<_> = [1, 2, 3].into_iter().collect();
+        let copy = list.clone();
+        assert_eq!(list, copy);
+        assert_eq!(format!("{:?}", copy), "[1, 2, 3]");
+
+        let mut longer = copy.clone();
+        longer.extend([4]);
+        assert_ne!(list, longer);
+        assert!(list < longer);
+
+        let mut other = LinkedList::new();
+        other.push(0);
+        assert!(other < list);
+        assert_eq!(list.cmp(&list.clone()), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn hashmap_key() {
+        let list1: LinkedList<i32> = (0..10).collect();
+        let list2: LinkedList<i32> = (1..11).collect();
+        let mut map = std::collections::HashMap::new();
+
+        assert_eq!(map.insert(list1.clone(), "list1"), None);
+        assert_eq!(map.insert(list2.clone(), "list2"), None);
+        assert_eq!(map.get(&list1), Some(&"list1"));
+        assert_eq!(map.get(&list2), Some(&"list2"));
+        assert_eq!(map.get(&LinkedList::new()), None);
+    }
 }
@@ -0,0 +1,205 @@
+use crate::immutable_linked_list::ImmutableList;
+use std::fmt;
+
+/// Persistent cursor into an `ImmutableList`. The elements before the focus are kept reversed,
+/// nearest first, and the focus is the first element of the rest. Moving and editing touch only
+/// the nodes next to the focus, so every step is O(1) and returns a new zipper that shares the
+/// rest with the old one.
+///
+/// The focus may also sit just past the last element, where there is nothing to replace or
+/// delete but `insert` appends.
+pub struct ListZipper<T> {
+    left: ImmutableList<T>,
+    right: ImmutableList<T>,
+}
+
+impl<T> ListZipper<T> {
+    /// Focuses on the first element of `list`.
+    pub fn new(list: &ImmutableList<T>) -> Self {
+        ListZipper {
+            left: ImmutableList::new(),
+            right: list.clone(),
+        }
+    }
+
+    /// `None` once the zipper has moved past the last element.
+    pub fn focus(&self) -> Option<&T> {
+        self.right.first()
+    }
+
+    pub fn index(&self) -> usize {
+        self.left.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.left.len() + self.right.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty() && self.right.is_empty()
+    }
+}
+
+impl<T: Clone> ListZipper<T> {
+    /// Moves the focus one element towards the front, or returns `None` at the first one.
+    pub fn left(&self) -> Option<Self> {
+        self.left.uncons().map(|(value, left)| ListZipper {
+            left,
+            right: self.right.prepend(value.clone()),
+        })
+    }
+
+    /// Moves the focus one element towards the back, possibly past the last one. Returns `None`
+    /// if it is already there.
+    pub fn right(&self) -> Option<Self> {
+        self.right.uncons().map(|(value, right)| ListZipper {
+            left: self.left.prepend(value.clone()),
+            right,
+        })
+    }
+
+    /// Swaps the focused element for `value`, or returns `None` past the last element.
+    pub fn replace(&self, value: T) -> Option<Self> {
+        self.focus()?;
+        Some(ListZipper {
+            left: self.left.clone(),
+            right: self.right.tail().prepend(value),
+        })
+    }
+
+    /// Inserts `value` before the focus and focuses on it.
+    pub fn insert(&self, value: T) -> Self {
+        ListZipper {
+            left: self.left.clone(),
+            right: self.right.prepend(value),
+        }
+    }
+
+    /// Removes the focused element and focuses on the one after it, or returns `None` past the
+    /// last element.
+    pub fn delete(&self) -> Option<Self> {
+        self.focus()?;
+        Some(ListZipper {
+            left: self.left.clone(),
+            right: self.right.tail(),
+        })
+    }
+
+    /// Rebuilds the whole list. The part before the focus is copied, the rest is shared.
+    pub fn to_list(&self) -> ImmutableList<T> {
+        self.left.iter().fold(self.right.clone(), |list, value| {
+            list.prepend(value.clone())
+        })
+    }
+}
+
+/// O(1): the clone shares everything with `self`.
+impl<T> Clone for ListZipper<T> {
+    fn clone(&self) -> Self {
+        ListZipper {
+            left: self.left.clone(),
+            right: self.right.clone(),
+        }
+    }
+}
+
+impl<T> Default for ListZipper<T> {
+    fn default() -> Self {
+        Self::new(&ImmutableList::new())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ListZipper<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ListZipper")
+            .field("index", &self.index())
+            .field("focus", &self.focus())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ListZipper;
+    use crate::immutable_linked_list::ImmutableList;
+
+    fn to_vec(zipper: &ListZipper<i32>) -> Vec<i32> {
+        zipper.to_list().iter().copied().collect()
+    }
+
+    #[test]
+    fn navigation() {
+        let list: ImmutableList<_> = (1..=3).collect();
+        let start = ListZipper::new(&list);
+        assert_eq!(start.focus(), Some(&1));
+        assert_eq!(start.index(), 0);
+        assert_eq!(start.len(), 3);
+        assert!(start.left().is_none());
+
+        let second = start.right().unwrap();
+        assert_eq!(second.focus(), Some(&2));
+        assert_eq!(second.left().unwrap().focus(), Some(&1));
+
+        let end = second.right().unwrap().right().unwrap();
+        assert_eq!(end.focus(), None);
+        assert_eq!(end.index(), 3);
+        assert!(end.right().is_none());
+        assert_eq!(end.left().unwrap().focus(), Some(&3));
+        assert_eq!(to_vec(&end), [1, 2, 3]);
+
+        let empty = ListZipper::<i32>::default();
+        assert!(empty.is_empty());
+        assert!(empty.left().is_none());
+        assert!(empty.right().is_none());
+        assert_eq!(
+            format!("{:?}", empty),
+            "ListZipper { index: 0, focus: None, .. }"
+        );
+    }
+
+    #[test]
+    fn editing() {
+        let list: ImmutableList<_> = (1..=4).collect();
+        let zipper = ListZipper::new(&list).right().unwrap();
+
+        let replaced = zipper.replace(20).unwrap();
+        assert_eq!(replaced.focus(), Some(&20));
+        assert_eq!(to_vec(&replaced), [1, 20, 3, 4]);
+
+        let inserted = replaced.insert(15);
+        assert_eq!(inserted.focus(), Some(&15));
+        assert_eq!(inserted.index(), 1);
+        assert_eq!(to_vec(&inserted), [1, 15, 20, 3, 4]);
+
+        let deleted = inserted.delete().unwrap().delete().unwrap();
+        assert_eq!(deleted.focus(), Some(&3));
+        assert_eq!(to_vec(&deleted), [1, 3, 4]);
+
+        // Editing past the last element
+        let end = deleted.right().unwrap().right().unwrap();
+        assert!(end.replace(0).is_none());
+        assert!(end.delete().is_none());
+        assert_eq!(to_vec(&end.insert(5)), [1, 3, 4, 5]);
+
+        // Every older version is untouched
+        assert_eq!(to_vec(&zipper), [1, 2, 3, 4]);
+        assert_eq!(to_vec(&replaced), [1, 20, 3, 4]);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn undo_stack() {
+        let mut history = vec![ListZipper::new(&ImmutableList::new())];
+        for i in 0..5 {
+            let next = history.last().unwrap().insert(i).right().unwrap();
+            history.push(next);
+        }
+        assert_eq!(to_vec(history.last().unwrap()), [0, 1, 2, 3, 4]);
+
+        // Undo two steps and take a different branch
+        let branch = history[3].insert(10);
+        assert_eq!(to_vec(&branch), [0, 1, 2, 10]);
+        assert_eq!(to_vec(&history[5]), [0, 1, 2, 3, 4]);
+        assert_eq!(branch.to_list(), (0..3).chain([10]).collect());
+    }
+}
@@ -0,0 +1,352 @@
+use crate::unsafe_linked_list::{NodeHandle, UnsafeLinkedList};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::{fmt, mem};
+
+/// Least-recently-used cache. Entries live in an `UnsafeLinkedList` ordered from most to least
+/// recently used, and the map points straight at their nodes so a hit is relinked in O(1).
+pub struct LruCache<K, V> {
+    map: HashMap<K, NodeHandle<(K, V)>>,
+    list: UnsafeLinkedList<(K, V)>,
+    capacity: usize,
+    on_evict: Option<Box<dyn FnMut(K, V) + Send>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "LruCache capacity must be non-zero");
+        LruCache {
+            map: HashMap::with_capacity(capacity),
+            list: UnsafeLinkedList::new(),
+            capacity,
+            on_evict: None,
+        }
+    }
+
+    /// `on_evict` sees every entry dropped to make room, whether by `put` or `resize`. Entries
+    /// taken out through `pop_lru`, `remove` or `clear` are not reported.
+    pub fn with_on_evict(capacity: usize, on_evict: impl FnMut(K, V) + Send + 'static) -> Self {
+        LruCache {
+            on_evict: Some(Box::new(on_evict)),
+            ..Self::new(capacity)
+        }
+    }
+
+    /// Marks the entry as most recently used.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let node = self.map.get(key)?;
+        unsafe {
+            self.list.handle_move_to_front(node);
+            Some(&self.list.handle_get(node).1)
+        }
+    }
+
+    /// Marks the entry as most recently used.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let node = self.map.get(key)?;
+        unsafe {
+            self.list.handle_move_to_front(node);
+            Some(&mut self.list.handle_get_mut(node).1)
+        }
+    }
+
+    /// Looks the entry up without touching its recency.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let node = self.map.get(key)?;
+        unsafe { Some(&self.list.handle_get(node).1) }
+    }
+
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        self.list.back().map(|(key, value)| (key, value))
+    }
+
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts or updates `key` as the most recently used entry and returns the value it
+    /// replaced. Evicts the least recently used entry if the cache was full.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(node) = self.map.get(&key) {
+            unsafe {
+                self.list.handle_move_to_front(node);
+                return Some(mem::replace(&mut self.list.handle_get_mut(node).1, value));
+            }
+        }
+
+        let node = self.list.push_handle((key.clone(), value));
+        self.map.insert(key, node);
+        self.evict_to(self.capacity);
+        None
+    }
+
+    /// Panics if the key's `Hash` or `Eq` changed while it was in the cache, since the map can
+    /// then no longer find its entry.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let back = self.list.back_handle()?;
+        unsafe {
+            // Only free the node through the handle the map gives up, or the map could keep
+            // pointing at it
+            let node = self.map.remove(&self.list.handle_get(&back).0);
+            let node = node
+                .filter(|node| self.list.handle_is_back(node))
+                .expect("LruCache key's Hash or Eq changed while it was in the cache");
+            Some(self.list.handle_remove(node))
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let node = self.map.remove(key)?;
+        unsafe { Some(self.list.handle_remove(node).1) }
+    }
+
+    /// Shrinking evicts least recently used entries until the cache fits.
+    pub fn resize(&mut self, capacity: usize) {
+        assert!(capacity > 0, "LruCache capacity must be non-zero");
+        self.capacity = capacity;
+        self.evict_to(capacity);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.list.clear();
+    }
+
+    /// Iterates from the most to the least recently used entry.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator {
+        self.list.iter().map(|(key, value)| (key, value))
+    }
+
+    fn evict_to(&mut self, capacity: usize) {
+        while self.list.len() > capacity {
+            if let Some((key, value)) = self.pop_lru() {
+                if let Some(on_evict) = self.on_evict.as_mut() {
+                    on_evict(key, value);
+                }
+            }
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.list.iter().map(|(key, value)| (key, value)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LruCache;
+    use std::hash::{Hash, Hasher};
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    // Never equal to anything, itself included, so the map can't find it again
+    #[derive(Clone, Debug)]
+    struct Unequal(i32);
+
+    impl PartialEq for Unequal {
+        fn eq(&self, _: &Self) -> bool {
+            false
+        }
+    }
+
+    impl Eq for Unequal {}
+
+    impl Hash for Unequal {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.0.hash(state);
+        }
+    }
+
+    fn keys<V>(cache: &LruCache<&'static str, V>) -> Vec<&'static str> {
+        cache.iter().map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn basics() {
+        let mut cache = LruCache::new(2);
+        assert!(cache.is_empty());
+        assert_eq!(cache.put("a", 1), None);
+        assert_eq!(cache.put("b", 2), None);
+        assert_eq!(keys(&cache), ["b", "a"]);
+
+        // A hit moves the entry to the front
+        assert_eq!(cache.get("a"), Some(&1));
+        assert_eq!(keys(&cache), ["a", "b"]);
+
+        // A full cache drops the least recently used entry
+        assert_eq!(cache.put("c", 3), None);
+        assert_eq!(keys(&cache), ["c", "a"]);
+        assert!(!cache.contains("b"));
+        assert_eq!(cache.get("b"), None);
+
+        // Updating replaces the value and refreshes the entry
+        assert_eq!(cache.put("a", 10), Some(1));
+        assert_eq!(keys(&cache), ["a", "c"]);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.capacity(), 2);
+    }
+
+    #[test]
+    fn peek_does_not_touch() {
+        let mut cache = LruCache::new(3);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.put("c", 3);
+
+        assert_eq!(cache.peek("a"), Some(&1));
+        assert_eq!(cache.peek_lru(), Some((&"a", &1)));
+        assert_eq!(keys(&cache), ["c", "b", "a"]);
+
+        *cache.get_mut("a").unwrap() += 10;
+        assert_eq!(cache.peek_lru(), Some((&"b", &2)));
+        assert_eq!(cache.peek("a"), Some(&11));
+    }
+
+    #[test]
+    fn pop_and_remove() {
+        let mut cache = LruCache::new(4);
+        for (i, key) in ["a", "b", "c", "d"].into_iter().enumerate() {
+            cache.put(key, i);
+        }
+
+        assert_eq!(cache.pop_lru(), Some(("a", 0)));
+        assert_eq!(cache.remove("c"), Some(2));
+        assert_eq!(cache.remove("c"), None);
+        assert_eq!(keys(&cache), ["d", "b"]);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.pop_lru(), None);
+        cache.put("e", 4);
+        assert_eq!(keys(&cache), ["e"]);
+    }
+
+    #[test]
+    fn eviction_callback_and_resize() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let mut cache = {
+            let evicted = Arc::clone(&evicted);
+            LruCache::with_on_evict(3, move |key, value| {
+                evicted.lock().unwrap().push((key, value))
+            })
+        };
+
+        for (i, key) in ["a", "b", "c", "d"].into_iter().enumerate() {
+            cache.put(key, i);
+        }
+        assert_eq!(*evicted.lock().unwrap(), [("a", 0)]);
+
+        cache.get("b");
+        cache.resize(1);
+        assert_eq!(cache.capacity(), 1);
+        assert_eq!(*evicted.lock().unwrap(), [("a", 0), ("c", 2), ("d", 3)]);
+        assert_eq!(keys(&cache), ["b"]);
+
+        // Explicit removals are not evictions
+        cache.resize(5);
+        cache.put("e", 4);
+        cache.pop_lru();
+        cache.remove("e");
+        assert_eq!(evicted.lock().unwrap().len(), 3);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn owned_keys() {
+        let mut cache: LruCache<String, Vec<u8>> = LruCache::new(2);
+        cache.put("one".to_string(), vec![1]);
+        cache.put("two".to_string(), vec![2, 2]);
+        assert_eq!(cache.get("one").map(Vec::len), Some(1));
+        cache.put("three".to_string(), vec![3, 3, 3]);
+
+        assert!(cache.contains("one"));
+        assert!(!cache.contains("two"));
+        assert_eq!(
+            format!("{:?}", cache),
+            r#"{"three": [3, 3, 3], "one": [1]}"#
+        );
+    }
+
+    #[test]
+    fn moves_between_threads() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let mut cache = {
+            let evicted = Arc::clone(&evicted);
+            LruCache::with_on_evict(1, move |key, _| evicted.lock().unwrap().push(key))
+        };
+        cache.put("a", 1);
+
+        let cache = thread::spawn(move || {
+            cache.put("b", 2);
+            cache
+        })
+        .join()
+        .unwrap();
+        assert_eq!(keys(&cache), ["b"]);
+        assert_eq!(*evicted.lock().unwrap(), ["a"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity must be non-zero")]
+    fn zero_capacity() {
+        LruCache::<u8, u8>::new(0);
+    }
+
+    #[test]
+    fn broken_key_eq_panics_instead_of_freeing() {
+        let mut cache = LruCache::new(2);
+        cache.put(Unequal(1), 1);
+        cache.put(Unequal(2), 2);
+        let evicted = panic::catch_unwind(AssertUnwindSafe(|| cache.put(Unequal(3), 3)));
+        assert!(evicted.is_err());
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| cache.pop_lru())).is_err());
+
+        // Nothing was freed, every entry is still reachable
+        assert_eq!(cache.len(), 3);
+        assert_eq!(
+            cache.iter().map(|(_, value)| *value).collect::<Vec<_>>(),
+            [3, 2, 1]
+        );
+        assert_eq!(cache.peek(&Unequal(1)), None);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -1,10 +1,4 @@
-use crate::linked_list::LinkedList;
-
-mod immutable_linked_list;
-mod linked_list;
-mod safe_deque;
-mod unsafe_linked_list;
-mod unsafe_queue;
+use linked_list::linked_list::LinkedList;
 
 fn main() {
     let mut list = LinkedList::new();
@@ -0,0 +1,307 @@
+use crate::immutable_linked_list::{self, ImmutableList};
+use std::fmt;
+
+// Neither half may grow past this many times the other, plus one
+const BALANCE: usize = 3;
+
+/// Banker's deque: the front half in order and the back half reversed, both persistent lists.
+/// Every operation returns a new version and leaves the old one untouched. When one half gets
+/// too long it is split in two, which keeps pushes and pops O(1) amortized as long as versions
+/// are used linearly. Repeatedly popping from the same old version can redo the same rebalance.
+pub struct PersistentDeque<T> {
+    front: ImmutableList<T>,
+    back: ImmutableList<T>,
+}
+
+pub struct Iter<'a, T> {
+    front: immutable_linked_list::Iter<'a, T>,
+    back: std::iter::Rev<std::vec::IntoIter<&'a T>>,
+}
+
+impl<T> PersistentDeque<T> {
+    pub fn new() -> Self {
+        PersistentDeque {
+            front: ImmutableList::new(),
+            back: ImmutableList::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.front.is_empty() && self.back.is_empty()
+    }
+
+    pub fn peek_front(&self) -> Option<&T> {
+        // A balanced deque with an empty front holds at most one element, at the back
+        self.front.first().or_else(|| self.back.first())
+    }
+
+    pub fn peek_back(&self) -> Option<&T> {
+        self.back.first().or_else(|| self.front.first())
+    }
+
+    /// Front to back. The back half is singly linked the wrong way round, so starting the
+    /// iterator collects references to it.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.front.iter(),
+            back: self.back.iter().collect::<Vec<_>>().into_iter().rev(),
+        }
+    }
+}
+
+impl<T: Clone> PersistentDeque<T> {
+    pub fn push_front(&self, value: T) -> Self {
+        PersistentDeque {
+            front: self.front.prepend(value),
+            back: self.back.clone(),
+        }
+        .balanced()
+    }
+
+    pub fn push_back(&self, value: T) -> Self {
+        PersistentDeque {
+            front: self.front.clone(),
+            back: self.back.prepend(value),
+        }
+        .balanced()
+    }
+
+    pub fn pop_front(&self) -> Option<(&T, Self)> {
+        match self.front.uncons() {
+            Some((value, front)) => {
+                let rest = PersistentDeque {
+                    front,
+                    back: self.back.clone(),
+                };
+                Some((value, rest.balanced()))
+            }
+            None => self.back.first().map(|value| (value, Self::new())),
+        }
+    }
+
+    pub fn pop_back(&self) -> Option<(&T, Self)> {
+        match self.back.uncons() {
+            Some((value, back)) => {
+                let rest = PersistentDeque {
+                    front: self.front.clone(),
+                    back,
+                };
+                Some((value, rest.balanced()))
+            }
+            None => self.front.first().map(|value| (value, Self::new())),
+        }
+    }
+
+    /// Moves half of the elements over if either side got too long.
+    fn balanced(self) -> Self {
+        let (front, back) = (self.front.len(), self.back.len());
+        let len = front + back;
+        if front > BALANCE * back + 1 {
+            let keep = len.div_ceil(2);
+            PersistentDeque {
+                front: self.front.take(keep),
+                back: self.back.append(&self.front.skip(keep).reversed()),
+            }
+        } else if back > BALANCE * front + 1 {
+            let keep = len.div_ceil(2);
+            PersistentDeque {
+                front: self.front.append(&self.back.skip(keep).reversed()),
+                back: self.back.take(keep),
+            }
+        } else {
+            self
+        }
+    }
+}
+
+/// O(1): the clone shares both halves with `self`.
+impl<T> Clone for PersistentDeque<T> {
+    fn clone(&self) -> Self {
+        PersistentDeque {
+            front: self.front.clone(),
+            back: self.back.clone(),
+        }
+    }
+}
+
+impl<T> Default for PersistentDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> FromIterator<T> for PersistentDeque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        PersistentDeque {
+            front: iter.into_iter().collect(),
+            back: ImmutableList::new(),
+        }
+        .balanced()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentDeque<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {
+    fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentDeque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PersistentDeque, BALANCE};
+    use crate::rng::SplitMix64;
+    use std::collections::VecDeque;
+
+    fn to_vec(deque: &PersistentDeque<i32>) -> Vec<i32> {
+        deque.iter().copied().collect()
+    }
+
+    fn check_balance<T>(deque: &PersistentDeque<T>) {
+        let (front, back) = (deque.front.len(), deque.back.len());
+        assert!(front <= BALANCE * back + 1, "front {front}, back {back}");
+        assert!(back <= BALANCE * front + 1, "front {front}, back {back}");
+    }
+
+    #[test]
+    fn basics() {
+        let empty = PersistentDeque::new();
+        assert!(empty.is_empty());
+        assert!(empty.pop_front().is_none());
+        assert!(empty.pop_back().is_none());
+
+        let deque = empty.push_back(2).push_back(3).push_front(1);
+        assert_eq!(deque.len(), 3);
+        assert_eq!(deque.peek_front(), Some(&1));
+        assert_eq!(deque.peek_back(), Some(&3));
+        assert_eq!(format!("{:?}", deque), "[1, 2, 3]");
+
+        let (first, rest) = deque.pop_front().unwrap();
+        assert_eq!(*first, 1);
+        let (last, rest) = rest.pop_back().unwrap();
+        assert_eq!(*last, 3);
+        assert_eq!(to_vec(&rest), [2]);
+
+        // Popping the only element works from either side
+        assert_eq!(
+            rest.pop_front().map(|(value, rest)| (*value, rest.len())),
+            Some((2, 0))
+        );
+        assert_eq!(
+            rest.pop_back().map(|(value, rest)| (*value, rest.len())),
+            Some((2, 0))
+        );
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn one_sided_use_stays_balanced() {
+        // Queue use: push at the back, pop at the front
+        let mut deque = PersistentDeque::new();
+        for i in 0..100 {
+            deque = deque.push_back(i);
+            check_balance(&deque);
+        }
+        for i in 0..100 {
+            let (value, rest) = deque.pop_front().unwrap();
+            assert_eq!(*value, i);
+            deque = rest;
+            check_balance(&deque);
+        }
+        assert!(deque.is_empty());
+
+        let deque: PersistentDeque<_> = (0..50).collect();
+        check_balance(&deque);
+        assert_eq!(to_vec(&deque), (0..50).collect::<Vec<_>>());
+        assert_eq!(deque.peek_back(), Some(&49));
+    }
+
+    #[test]
+    fn old_versions_stay_valid() {
+        let mut versions = vec![PersistentDeque::new()];
+        for i in 0..20 {
+            let next = versions.last().unwrap().push_back(i);
+            versions.push(next);
+        }
+        for (len, version) in versions.iter().enumerate() {
+            assert_eq!(to_vec(version), (0..len as i32).collect::<Vec<_>>());
+        }
+
+        let full = versions.last().unwrap();
+        let (_, popped) = full.pop_front().unwrap();
+        let pushed = full.push_front(-1);
+        assert_eq!(to_vec(&popped), (1..20).collect::<Vec<_>>());
+        assert_eq!(to_vec(&pushed), (-1..20).collect::<Vec<_>>());
+        assert_eq!(to_vec(full), (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn matches_vec_deque() {
+        let mut rng = SplitMix64::new(7);
+        let mut model = VecDeque::new();
+        let mut deque = PersistentDeque::new();
+
+        for i in 0..2000 {
+            match rng.next_u64() % 4 {
+                0 => {
+                    model.push_front(i);
+                    deque = deque.push_front(i);
+                }
+                1 => {
+                    model.push_back(i);
+                    deque = deque.push_back(i);
+                }
+                2 => {
+                    let popped = deque.pop_front().map(|(value, rest)| (*value, rest));
+                    assert_eq!(popped.as_ref().map(|(value, _)| *value), model.pop_front());
+                    if let Some((_, rest)) = popped {
+                        deque = rest;
+                    }
+                }
+                _ => {
+                    let popped = deque.pop_back().map(|(value, rest)| (*value, rest));
+                    assert_eq!(popped.as_ref().map(|(value, _)| *value), model.pop_back());
+                    if let Some((_, rest)) = popped {
+                        deque = rest;
+                    }
+                }
+            }
+            check_balance(&deque);
+            assert_eq!(deque.len(), model.len());
+            assert_eq!(deque.peek_front(), model.front());
+            assert_eq!(deque.peek_back(), model.back());
+        }
+        assert!(deque.iter().eq(model.iter()));
+    }
+}
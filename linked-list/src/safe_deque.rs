@@ -1,9 +1,27 @@
 use std::cell::{Ref, RefCell, RefMut};
+use std::fmt;
 use std::rc::Rc;
 
-struct Deque<T> {
+pub struct Deque<T> {
     first: Link<T>,
     last: Link<T>,
+    len: usize,
+}
+
+pub struct IntoIter<T>(Deque<T>);
+
+/// Yields a `Ref` per element, so it panics if an element is mutably borrowed when reached.
+pub struct Iter<'a, T> {
+    front: Option<&'a RefCell<Node<T>>>,
+    back: Option<&'a RefCell<Node<T>>>,
+    len: usize,
+}
+
+/// Yields a `RefMut` per element, so it panics if an element is still borrowed when reached.
+pub struct IterMut<'a, T> {
+    front: Option<&'a RefCell<Node<T>>>,
+    back: Option<&'a RefCell<Node<T>>>,
+    len: usize,
 }
 
 struct Node<T> {
@@ -19,11 +37,13 @@ impl<T> Deque<T> {
         Deque {
             first: None,
             last: None,
+            len: 0,
         }
     }
 
     pub fn push_front(&mut self, value: T) {
         let new_first = Node::new(value);
+        self.len += 1;
         match self.first.take() {
             None => {
                 self.last = Some(new_first.clone());
@@ -39,6 +59,7 @@ impl<T> Deque<T> {
 
     pub fn push_back(&mut self, value: T) {
         let new_first = Node::new(value);
+        self.len += 1;
         match self.last.take() {
             None => {
                 self.first = Some(new_first.clone());
@@ -54,6 +75,7 @@ impl<T> Deque<T> {
 
     pub fn pop_front(&mut self) -> Option<T> {
         self.first.take().map(|old_node| {
+            self.len -= 1;
             match old_node.borrow_mut().next.take() {
                 None => {
                     self.last.take();
@@ -69,6 +91,7 @@ impl<T> Deque<T> {
 
     pub fn pop_back(&mut self) -> Option<T> {
         self.last.take().map(|old_node| {
+            self.len -= 1;
             match old_node.borrow_mut().prev.take() {
                 None => {
                     self.first.take();
@@ -82,29 +105,76 @@ impl<T> Deque<T> {
         })
     }
 
-    pub fn peek_front(&self) -> Option<Ref<T>> {
+    pub fn peek_front(&self) -> Option<Ref<'_, T>> {
         self.first
             .as_ref()
             .map(|node| Ref::map(node.borrow(), |rf| &rf.value))
     }
 
-    pub fn peek_front_mut(&self) -> Option<RefMut<T>> {
+    pub fn peek_front_mut(&self) -> Option<RefMut<'_, T>> {
         self.first
             .as_ref()
             .map(|node| RefMut::map(node.borrow_mut(), |rf| &mut rf.value))
     }
 
-    pub fn peek_back(&self) -> Option<Ref<T>> {
+    pub fn peek_back(&self) -> Option<Ref<'_, T>> {
         self.last
             .as_ref()
             .map(|node| Ref::map(node.borrow(), |rf| &rf.value))
     }
 
-    pub fn peek_back_mut(&self) -> Option<RefMut<T>> {
+    pub fn peek_back_mut(&self) -> Option<RefMut<'_, T>> {
         self.last
             .as_ref()
             .map(|node| RefMut::map(node.borrow_mut(), |rf| &mut rf.value))
     }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.first.is_none()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.first.as_deref(),
+            back: self.last.as_deref(),
+            len: self.len,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.first.as_deref(),
+            back: self.last.as_deref(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Node<T> {
+    // Links are read without going through the `RefCell`, so walking past a node never conflicts
+    // with a borrow of its value that the caller is still holding
+    fn next_of(node: &RefCell<Self>) -> Option<&RefCell<Self>> {
+        // SAFETY: the place expression only borrows the `next` field, which no `Ref`/`RefMut`
+        // handed out by the deque covers, and links only change through `&mut Deque`, which
+        // cannot coexist with the borrow of the deque that `node` comes from.
+        unsafe { (*node.as_ptr()).next.as_deref() }
+    }
+
+    fn prev_of(node: &RefCell<Self>) -> Option<&RefCell<Self>> {
+        // SAFETY: as in `next_of`, only the `prev` field is borrowed and it cannot change while
+        // the deque is borrowed.
+        unsafe { (*node.as_ptr()).prev.as_deref() }
+    }
+}
+
+impl<T> Default for Deque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<T> Node<T> {
@@ -123,6 +193,144 @@ impl<T> Drop for Deque<T> {
     }
 }
 
+impl<T> IntoIterator for Deque<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Deque<T> {
+    type Item = Ref<'a, T>;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Deque<T> {
+    type Item = RefMut<'a, T>;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = Ref<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.front.map(|node| {
+            self.len -= 1;
+            self.front = Node::next_of(node);
+            Ref::map(node.borrow(), |node| &node.value)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.back.map(|node| {
+            self.len -= 1;
+            self.back = Node::prev_of(node);
+            Ref::map(node.borrow(), |node| &node.value)
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = RefMut<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.front.map(|node| {
+            self.len -= 1;
+            self.front = Node::next_of(node);
+            RefMut::map(node.borrow_mut(), |node| &mut node.value)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.back.map(|node| {
+            self.len -= 1;
+            self.back = Node::prev_of(node);
+            RefMut::map(node.borrow_mut(), |node| &mut node.value)
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> Extend<T> for Deque<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for Deque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut deque = Self::new();
+        deque.extend(iter);
+        deque
+    }
+}
+
+/// Panics if an element is mutably borrowed while printing.
+impl<T: fmt::Debug> fmt::Debug for Deque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::safe_deque::Deque;
@@ -199,4 +407,129 @@ mod test {
         assert_eq!(&*list.peek_back().unwrap(), &1);
         assert_eq!(&mut *list.peek_back_mut().unwrap(), &mut 1);
     }
+
+    #[test]
+    fn into_iter() {
+        let list: Deque<_> = (1..=4).collect();
+        assert_eq!(list.len(), 4);
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next_back(), Some(4));
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.next_back(), Some(3));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn iter() {
+        let mut list = Deque::new();
+        assert_eq!(list.iter().next().map(|value| *value), None::<i32>);
+
+        list.extend([1, 2, 3]);
+        list.push_front(0);
+        assert_eq!(
+            list.iter().map(|value| *value).collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+        assert_eq!(
+            list.iter().rev().map(|value| *value).collect::<Vec<_>>(),
+            [3, 2, 1, 0]
+        );
+
+        // Both ends meet in the middle without yielding anything twice
+        let mut iter = list.iter();
+        assert_eq!(*iter.next().unwrap(), 0);
+        assert_eq!(*iter.next_back().unwrap(), 3);
+        assert_eq!(iter.len(), 2);
+        assert_eq!(*iter.next_back().unwrap(), 2);
+        assert_eq!(*iter.next().unwrap(), 1);
+        assert!(iter.next().is_none());
+        assert!(iter.next_back().is_none());
+
+        // Holding the guards is fine, they are shared borrows
+        let guards: Vec<_> = list.iter().collect();
+        assert_eq!(*list.peek_front().unwrap(), *guards[0]);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut list: Deque<_> = (0..5).collect();
+        for mut value in &mut list {
+            *value *= 10;
+        }
+        if let Some(mut last) = list.iter_mut().next_back() {
+            *last += 1;
+        }
+        assert_eq!(format!("{:?}", list), "[0, 10, 20, 30, 41]");
+
+        // Walking past a node that is still borrowed is fine
+        let mut iter = list.iter_mut();
+        let mut first = iter.next().unwrap();
+        let mut second = iter.next().unwrap();
+        *first += 1;
+        *second += 1;
+        drop((first, second));
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_front(), Some(11));
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn iter_interleaved_with_peek_mut() {
+        let list: Deque<_> = (0..4).collect();
+
+        // Hold a mutable borrow of the front value while walking past it from the back
+        let mut front = list.peek_front_mut().unwrap();
+        let mut iter = list.iter();
+        assert_eq!(*iter.next_back().unwrap(), 3);
+        assert_eq!(*iter.next_back().unwrap(), 2);
+        assert_eq!(*iter.next_back().unwrap(), 1);
+        *front += 10;
+        drop(front);
+        assert_eq!(*iter.next_back().unwrap(), 10);
+
+        // And the other way round, with an element borrowed behind the iterator
+        let mut iter = list.iter();
+        let first = iter.next().unwrap();
+        let mut back = list.peek_back_mut().unwrap();
+        *back += 10;
+        assert_eq!(*iter.next().unwrap(), 1);
+        assert_eq!(*iter.next().unwrap(), 2);
+        drop(back);
+        assert_eq!(*iter.next().unwrap(), 13);
+        assert_eq!(*first, 10);
+    }
+
+    #[test]
+    fn iter_mut_interleaved_with_peek_mut() {
+        let mut list: Deque<_> = (0..4).collect();
+        let mut iter = list.iter_mut();
+        let mut first = iter.next().unwrap();
+        let mut last = iter.next_back().unwrap();
+        for mut value in iter {
+            *value *= 10;
+        }
+        *first -= 1;
+        *last += 1;
+        drop((first, last));
+
+        let mut back = list.peek_back_mut().unwrap();
+        *back += 1;
+        drop(back);
+        assert_eq!(
+            list.iter().map(|value| *value).collect::<Vec<_>>(),
+            [-1, 10, 20, 5]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "already mutably borrowed")]
+    fn iter_checks_borrows() {
+        let list: Deque<_> = (0..3).collect();
+        let _front = list.peek_front_mut();
+        list.iter().for_each(drop);
+    }
 }
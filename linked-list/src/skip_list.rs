@@ -0,0 +1,744 @@
+use crate::rng::SplitMix64;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+use std::ptr::NonNull;
+use std::{iter, mem};
+
+const MAX_LEVEL: usize = 32;
+const DEFAULT_SEED: u64 = 0x5EED_5EED_5EED_5EED;
+
+/**
+run tests:
+MIRIFLAGS="-Zmiri-tag-raw-pointers" cargo  +nightly-2023-06-18 miri test
+ */
+pub struct SkipList<K, V> {
+    head: [Link<K, V>; MAX_LEVEL],
+    last: Link<K, V>,
+    level: usize,
+    len: usize,
+    rng: SplitMix64,
+    _boo: PhantomData<Box<Node<K, V>>>,
+}
+
+pub struct Iter<'a, K, V> {
+    front: Link<K, V>,
+    back: Link<K, V>,
+    _boo: PhantomData<&'a Node<K, V>>,
+}
+
+pub struct IterMut<'a, K, V> {
+    front: Link<K, V>,
+    back: Link<K, V>,
+    _boo: PhantomData<&'a mut Node<K, V>>,
+}
+
+pub struct IntoIter<K, V> {
+    list: SkipList<K, V>,
+}
+
+type Link<K, V> = Option<NonNull<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    back: Link<K, V>,
+    forward: Box<[Link<K, V>]>,
+}
+
+unsafe impl<K: Send, V: Send> Send for SkipList<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for SkipList<K, V> {}
+
+impl<K, V> SkipList<K, V> {
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+
+    /// Node heights are drawn from a generator seeded with `seed`, so the same seed and the same
+    /// operations always build the same shape.
+    pub fn with_seed(seed: u64) -> Self {
+        SkipList {
+            head: [None; MAX_LEVEL],
+            last: None,
+            level: 0,
+            len: 0,
+            rng: SplitMix64::new(seed),
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_first().is_some() {}
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        unsafe { self.head[0].map(|node| (&(*node.as_ptr()).key, &(*node.as_ptr()).value)) }
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        unsafe {
+            self.last
+                .map(|node| (&(*node.as_ptr()).key, &(*node.as_ptr()).value))
+        }
+    }
+
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        self.head[0].map(|node| unsafe {
+            // The first node is preceded by the head on every level it occupies
+            for (level, next) in (*node.as_ptr()).forward.iter().enumerate() {
+                self.head[level] = *next;
+            }
+            self.unlink_back(node);
+            Self::free(node)
+        })
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            front: self.head[0],
+            back: self.last,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            front: self.head[0],
+            back: self.last,
+            _boo: PhantomData,
+        }
+    }
+
+    fn next_at(&self, pred: Link<K, V>, level: usize) -> Link<K, V> {
+        match pred {
+            Some(node) => unsafe { (*node.as_ptr()).forward[level] },
+            None => self.head[level],
+        }
+    }
+
+    fn set_next(&mut self, pred: Link<K, V>, level: usize, next: Link<K, V>) {
+        match pred {
+            Some(node) => unsafe { (*node.as_ptr()).forward[level] = next },
+            None => self.head[level] = next,
+        }
+    }
+
+    /// Last node for which `before` holds, with `before` true for a prefix of the list. The
+    /// returned array holds that node for every level, `None` standing for the head.
+    fn predecessors(&self, mut before: impl FnMut(&K) -> bool) -> [Link<K, V>; MAX_LEVEL] {
+        let mut preds = [None; MAX_LEVEL];
+        let mut pred = None;
+        for level in (0..self.level).rev() {
+            while let Some(next) = self.next_at(pred, level) {
+                if !before(unsafe { &(*next.as_ptr()).key }) {
+                    break;
+                }
+                pred = Some(next);
+            }
+            preds[level] = pred;
+        }
+        preds
+    }
+
+    fn random_level(&mut self) -> usize {
+        // Each extra level is kept with probability 1/2
+        (self.rng.next_u64().trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+
+    unsafe fn unlink_back(&mut self, node: NonNull<Node<K, V>>) {
+        let back = (*node.as_ptr()).back;
+        match (*node.as_ptr()).forward[0] {
+            Some(next) => (*next.as_ptr()).back = back,
+            None => self.last = back,
+        }
+        while self.level > 0 && self.head[self.level - 1].is_none() {
+            self.level -= 1;
+        }
+        self.len -= 1;
+    }
+
+    unsafe fn free(node: NonNull<Node<K, V>>) -> (K, V) {
+        let boxed = Box::from_raw(node.as_ptr());
+        (boxed.key, boxed.value)
+    }
+}
+
+impl<K: Ord, V> SkipList<K, V> {
+    /// Returns the previous value if `key` was already present. The stored key is kept.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let preds = self.predecessors(|k| *k < key);
+        if let Some(next) = self.next_at(preds[0], 0) {
+            let next = unsafe { &mut *next.as_ptr() };
+            if next.key == key {
+                return Some(mem::replace(&mut next.value, value));
+            }
+        }
+
+        let height = self.random_level();
+        self.level = self.level.max(height);
+        let node = NonNull::from(Box::leak(Box::new(Node {
+            key,
+            value,
+            back: preds[0],
+            forward: vec![None; height].into_boxed_slice(),
+        })));
+
+        for (level, &pred) in preds.iter().enumerate().take(height) {
+            let next = self.next_at(pred, level);
+            unsafe { (*node.as_ptr()).forward[level] = next };
+            self.set_next(pred, level, Some(node));
+        }
+        unsafe {
+            match (*node.as_ptr()).forward[0] {
+                Some(next) => (*next.as_ptr()).back = Some(node),
+                None => self.last = Some(node),
+            }
+        }
+        self.len += 1;
+        None
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key)
+            .map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key)
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let preds = self.predecessors(|k| k.borrow() < key);
+        let node = self.next_at(preds[0], 0)?;
+        unsafe {
+            if (*node.as_ptr()).key.borrow() != key {
+                return None;
+            }
+            for (level, next) in (*node.as_ptr()).forward.iter().enumerate() {
+                self.set_next(preds[level], level, *next);
+            }
+            self.unlink_back(node);
+            Some(Self::free(node).1)
+        }
+    }
+
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let last = self.last?;
+        let preds = self.predecessors(|k| unsafe { k < &(*last.as_ptr()).key });
+        unsafe {
+            let height = (*last.as_ptr()).forward.as_ref().len();
+            for (level, &pred) in preds.iter().enumerate().take(height) {
+                self.set_next(pred, level, None);
+            }
+            self.unlink_back(last);
+            Some(Self::free(last))
+        }
+    }
+
+    /// Iterates in key order over the entries whose keys fall in `range`.
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let front = match range.start_bound() {
+            Bound::Included(start) => self.next_at(self.predecessors(|k| k.borrow() < start)[0], 0),
+            Bound::Excluded(start) => {
+                self.next_at(self.predecessors(|k| k.borrow() <= start)[0], 0)
+            }
+            Bound::Unbounded => self.head[0],
+        };
+        let back = match range.end_bound() {
+            Bound::Included(end) => self.predecessors(|k| k.borrow() <= end)[0],
+            Bound::Excluded(end) => self.predecessors(|k| k.borrow() < end)[0],
+            Bound::Unbounded => self.last,
+        };
+
+        let empty = match (front, back) {
+            (Some(front), Some(back)) => unsafe { (*front.as_ptr()).key > (*back.as_ptr()).key },
+            _ => true,
+        };
+        if empty {
+            Iter {
+                front: None,
+                back: None,
+                _boo: PhantomData,
+            }
+        } else {
+            Iter {
+                front,
+                back,
+                _boo: PhantomData,
+            }
+        }
+    }
+
+    fn find<Q>(&self, key: &Q) -> Link<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let pred = self.predecessors(|k| k.borrow() < key)[0];
+        self.next_at(pred, 0)
+            .filter(|node| unsafe { (*node.as_ptr()).key.borrow() == key })
+    }
+}
+
+impl<K, V> Drop for SkipList<K, V> {
+    fn drop(&mut self) {
+        let mut cur = self.head[0];
+        while let Some(node) = cur {
+            unsafe {
+                cur = (*node.as_ptr()).forward[0];
+                drop(Box::from_raw(node.as_ptr()));
+            }
+        }
+    }
+}
+
+impl<K, V> Default for SkipList<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Clone for SkipList<K, V> {
+    fn clone(&self) -> Self {
+        let mut new_list = SkipList::new();
+        new_list.rng = self.rng.clone();
+        new_list.extend(self.iter().map(|(k, v)| (k.clone(), v.clone())));
+        new_list
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipList<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipList<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for SkipList<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for SkipList<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for SkipList<K, V> {}
+
+impl<K: PartialOrd, V: PartialOrd> PartialOrd for SkipList<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<K: Ord, V: Ord> Ord for SkipList<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<K: Hash, V: Hash> Hash for SkipList<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SkipList<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut SkipList<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: Ord, V> IntoIterator for SkipList<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.front?;
+        if self.front == self.back {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.front = unsafe { (*node.as_ptr()).forward[0] };
+        }
+        unsafe { Some((&(*node.as_ptr()).key, &(*node.as_ptr()).value)) }
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back?;
+        if self.front == self.back {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.back = unsafe { (*node.as_ptr()).back };
+        }
+        unsafe { Some((&(*node.as_ptr()).key, &(*node.as_ptr()).value)) }
+    }
+}
+
+impl<K, V> iter::FusedIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.front?;
+        if self.front == self.back {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.front = unsafe { (*node.as_ptr()).forward[0] };
+        }
+        unsafe { Some((&(*node.as_ptr()).key, &mut (*node.as_ptr()).value)) }
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back?;
+        if self.front == self.back {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.back = unsafe { (*node.as_ptr()).back };
+        }
+        unsafe { Some((&(*node.as_ptr()).key, &mut (*node.as_ptr()).value)) }
+    }
+}
+
+impl<K: Ord, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_first()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<K: Ord, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_last()
+    }
+}
+
+impl<K: Ord, V> ExactSizeIterator for IntoIter<K, V> {}
+
+#[cfg(test)]
+mod test {
+    use super::{Link, SkipList};
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::BTreeMap;
+    use std::hash::{Hash, Hasher};
+    use std::ops::Bound;
+
+    fn check_links<K: Ord, V>(list: &SkipList<K, V>) {
+        unsafe {
+            // Level 0 is sorted and its back links mirror the forward links
+            let mut len = 0;
+            let mut prev: Link<K, V> = None;
+            let mut cur = list.head[0];
+            while let Some(node) = cur {
+                assert_eq!((*node.as_ptr()).back, prev);
+                if let Some(prev) = prev {
+                    assert!((*prev.as_ptr()).key < (*node.as_ptr()).key);
+                }
+                prev = cur;
+                cur = (*node.as_ptr()).forward[0];
+                len += 1;
+            }
+            assert_eq!(list.last, prev);
+            assert_eq!(list.len, len);
+
+            // Every higher level is a sorted subsequence of the one below
+            for level in 1..list.head.len() {
+                let mut cur = list.head[level];
+                if level >= list.level {
+                    assert!(cur.is_none());
+                }
+                while let Some(node) = cur {
+                    assert!((*node.as_ptr()).forward.as_ref().len() > level);
+                    let next = (*node.as_ptr()).forward[level];
+                    if let Some(next) = next {
+                        assert!((*node.as_ptr()).key < (*next.as_ptr()).key);
+                    }
+                    cur = next;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn basics() {
+        let mut list = SkipList::new();
+        assert!(list.is_empty());
+        assert_eq!(list.first(), None);
+        assert_eq!(list.last(), None);
+        assert_eq!(list.get(&1), None);
+
+        assert_eq!(list.insert(3, "c"), None);
+        assert_eq!(list.insert(1, "a"), None);
+        assert_eq!(list.insert(2, "b"), None);
+        assert_eq!(list.insert(2, "B"), Some("b"));
+        check_links(&list);
+
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.get(&2), Some(&"B"));
+        assert_eq!(list.first(), Some((&1, &"a")));
+        assert_eq!(list.last(), Some((&3, &"c")));
+
+        *list.get_mut(&1).unwrap() = "A";
+        assert_eq!(list.remove(&1), Some("A"));
+        assert_eq!(list.remove(&1), None);
+        assert_eq!(list.remove(&7), None);
+        check_links(&list);
+
+        assert_eq!(list.pop_last(), Some((3, "c")));
+        assert_eq!(list.pop_first(), Some((2, "B")));
+        assert_eq!(list.pop_first(), None);
+        check_links(&list);
+    }
+
+    #[test]
+    fn borrowed_lookup() {
+        let list: SkipList<String, usize> = ["pear", "apple", "fig"]
+            .iter()
+            .map(|s| (s.to_string(), s.len()))
+            .collect();
+
+        assert_eq!(list.get("fig"), Some(&3));
+        assert!(list.contains_key("apple"));
+        assert!(!list.contains_key("kiwi"));
+        let keys: Vec<_> = list
+            .range::<str, _>((Bound::Included("b"), Bound::Excluded("p")))
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(keys, ["fig"]);
+    }
+
+    #[test]
+    fn matches_btree_map() {
+        let mut list = SkipList::with_seed(7);
+        let mut model = BTreeMap::new();
+        let mut rng = crate::rng::SplitMix64::new(99);
+
+        for _ in 0..2_000 {
+            let key = rng.next_u64() % 300;
+            if rng.next_u64().is_multiple_of(3) {
+                assert_eq!(list.remove(&key), model.remove(&key));
+            } else {
+                assert_eq!(list.insert(key, key * 10), model.insert(key, key * 10));
+            }
+        }
+        check_links(&list);
+        assert_eq!(list.len(), model.len());
+        assert!(list.iter().eq(model.iter()));
+        assert!(list.iter().rev().eq(model.iter().rev()));
+        assert_eq!(list.first(), model.first_key_value());
+        assert_eq!(list.last(), model.last_key_value());
+
+        let bounds = [
+            (Bound::Unbounded, Bound::Unbounded),
+            (Bound::Included(10), Bound::Excluded(120)),
+            (Bound::Excluded(10), Bound::Included(120)),
+            (Bound::Included(299), Bound::Unbounded),
+            (Bound::Excluded(299), Bound::Unbounded),
+            (Bound::Included(40), Bound::Included(40)),
+            (Bound::Unbounded, Bound::Excluded(0)),
+        ];
+        for range in bounds {
+            assert!(list.range(range).eq(model.range(range)), "{:?}", range);
+            assert!(list.range(range).rev().eq(model.range(range).rev()));
+        }
+
+        let mut forward = list.range(100..200);
+        let mut expected = model.range(100..200);
+        while let Some(front) = forward.next() {
+            assert_eq!(Some(front), expected.next());
+            assert_eq!(forward.next_back(), expected.next_back());
+        }
+        assert_eq!(expected.next(), None);
+    }
+
+    #[test]
+    fn empty_and_inverted_ranges() {
+        let list: SkipList<_, _> = (0..10).map(|i| (i * 2, ())).collect();
+        assert_eq!(list.range(3..4).count(), 0);
+        assert_eq!(list.range(30..).count(), 0);
+        assert_eq!(list.range(..0).count(), 0);
+        assert_eq!(
+            list.range((Bound::Excluded(4), Bound::Excluded(6))).count(),
+            0
+        );
+        assert_eq!(list.range(4..=4).count(), 1);
+    }
+
+    #[test]
+    fn seeded_shape_is_deterministic() {
+        let heights = |seed| {
+            let list: SkipList<_, _> = {
+                let mut list = SkipList::with_seed(seed);
+                list.extend((0..200).map(|i| (i, i)));
+                list
+            };
+            let mut heights = Vec::new();
+            let mut cur = list.head[0];
+            while let Some(node) = cur {
+                unsafe {
+                    heights.push((*node.as_ptr()).forward.as_ref().len());
+                    cur = (*node.as_ptr()).forward[0];
+                }
+            }
+            heights
+        };
+
+        assert_eq!(heights(1), heights(1));
+        assert_ne!(heights(1), heights(2));
+        assert!(heights(1).iter().any(|&height| height > 1));
+    }
+
+    #[test]
+    fn iter_mut_and_into_iter() {
+        let mut list: SkipList<_, _> = (1..=5).map(|i| (i, i)).collect();
+        for (_, value) in &mut list {
+            *value *= 10;
+        }
+        assert_eq!(
+            list.iter_mut()
+                .rev()
+                .map(|(k, v)| (*k, *v))
+                .collect::<Vec<_>>(),
+            [(5, 50), (4, 40), (3, 30), (2, 20), (1, 10)]
+        );
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next(), Some((1, 10)));
+        assert_eq!(iter.next_back(), Some((5, 50)));
+        assert_eq!(iter.collect::<Vec<_>>(), [(2, 20), (3, 30), (4, 40)]);
+    }
+
+    #[test]
+    fn traits() {
+        let a: SkipList<_, _> = [(2, 'b'), (1, 'a')].into_iter().collect();
+        let mut b = SkipList::with_seed(3);
+        b.insert(1, 'a');
+        b.insert(2, 'b');
+        let c = a.clone();
+
+        assert_eq!(a, b);
+        assert_eq!(a, c);
+        assert_eq!(format!("{:?}", a), "{1: 'a', 2: 'b'}");
+
+        let hash = |list: &SkipList<i32, char>| {
+            let mut hasher = DefaultHasher::new();
+            list.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&a), hash(&b));
+
+        b.insert(3, 'c');
+        assert_ne!(a, b);
+        assert!(a < b);
+        assert_ne!(hash(&a), hash(&b));
+    }
+
+    #[test]
+    fn drops_values() {
+        use std::rc::Rc;
+
+        let marker = Rc::new(());
+        {
+            let mut list = SkipList::new();
+            for i in 0..100 {
+                list.insert(i, Rc::clone(&marker));
+            }
+            list.remove(&3);
+            list.pop_last();
+            let _rest: Vec<_> = list.range(10..20).collect();
+        }
+        assert_eq!(Rc::strong_count(&marker), 1);
+    }
+}
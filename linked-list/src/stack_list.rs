@@ -0,0 +1,135 @@
+// https://rust-unofficial.github.io/too-many-lists/infinity-stack-allocated.html
+pub struct StackList<'a, T> {
+    value: T,
+    prev: Option<&'a StackList<'a, T>>,
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a StackList<'a, T>>,
+}
+
+pub struct Frames<'a, T> {
+    next: Option<&'a StackList<'a, T>>,
+}
+
+impl<'a, T> StackList<'a, T> {
+    pub fn root<U>(value: T, callback: impl FnOnce(&StackList<'_, T>) -> U) -> U {
+        let list = StackList { value, prev: None };
+        callback(&list)
+    }
+
+    pub fn push<U>(&self, value: T, callback: impl FnOnce(&StackList<'_, T>) -> U) -> U {
+        let list = StackList {
+            value,
+            prev: Some(self),
+        };
+        callback(&list)
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn prev(&self) -> Option<&'a StackList<'a, T>> {
+        self.prev
+    }
+
+    pub fn depth(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: Some(self) }
+    }
+
+    pub fn frames(&self) -> Frames<'_, T> {
+        Frames { next: Some(self) }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a StackList<'a, T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.prev;
+            &node.value
+        })
+    }
+}
+
+impl<'a, T> Iterator for Frames<'a, T> {
+    type Item = &'a StackList<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next?;
+        self.next = node.prev;
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StackList;
+
+    #[test]
+    fn basics() {
+        StackList::root(1, |list| {
+            assert_eq!(list.value(), &1);
+            assert!(list.prev().is_none());
+            assert_eq!(list.depth(), 1);
+
+            list.push(2, |list| {
+                list.push(3, |list| {
+                    assert_eq!(list.value(), &3);
+                    assert_eq!(list.prev().map(|prev| *prev.value()), Some(2));
+                    assert_eq!(list.depth(), 3);
+                    assert_eq!(list.iter().copied().collect::<Vec<_>>(), [3, 2, 1]);
+                    assert_eq!(
+                        list.frames().map(|frame| frame.depth()).collect::<Vec<_>>(),
+                        [3, 2, 1]
+                    );
+                });
+
+                // The frame for 3 is gone, the rest is untouched
+                assert_eq!(list.iter().copied().collect::<Vec<_>>(), [2, 1]);
+            });
+        });
+    }
+
+    #[test]
+    fn branches_share_frames() {
+        StackList::root("root", |root| {
+            let left = root.push("left", |list| list.iter().copied().collect::<Vec<_>>());
+            let right = root.push("right", |list| list.iter().copied().collect::<Vec<_>>());
+
+            assert_eq!(left, ["left", "root"]);
+            assert_eq!(right, ["right", "root"]);
+        });
+    }
+
+    #[test]
+    fn recursion() {
+        fn depth_path(remaining: u32, path: &StackList<'_, u32>) -> Vec<u32> {
+            if remaining == 0 {
+                let mut out: Vec<_> = path.into_iter().copied().collect();
+                out.reverse();
+                out
+            } else {
+                path.push(*path.value() + 1, |path| depth_path(remaining - 1, path))
+            }
+        }
+
+        let path = StackList::root(0, |list| depth_path(4, list));
+        assert_eq!(path, [0, 1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,315 @@
+use crate::allocator::NodeAllocator;
+use crate::immutable_arc_list::ArcList;
+use crate::immutable_linked_list::ImmutableList;
+use crate::linked_list::LinkedList;
+use crate::safe_deque;
+use crate::unsafe_linked_list::UnsafeLinkedList;
+use crate::unsafe_queue::UnsafeQueue;
+
+/// LIFO access: `pop` returns the most recently pushed value.
+pub trait Stack<T> {
+    fn push(&mut self, value: T);
+
+    fn pop(&mut self) -> Option<T>;
+
+    fn peek(&self) -> Option<&T>;
+
+    fn peek_mut(&mut self) -> Option<&mut T>;
+
+    fn is_empty(&self) -> bool {
+        self.peek().is_none()
+    }
+}
+
+/// FIFO access: `pop` returns the least recently pushed value.
+pub trait Queue<T> {
+    fn push(&mut self, value: T);
+
+    fn pop(&mut self) -> Option<T>;
+
+    fn peek(&self) -> Option<&T>;
+
+    fn peek_mut(&mut self) -> Option<&mut T>;
+
+    fn is_empty(&self) -> bool {
+        self.peek().is_none()
+    }
+}
+
+/// Push and pop at both ends.
+pub trait Deque<T> {
+    fn push_front(&mut self, value: T);
+
+    fn push_back(&mut self, value: T);
+
+    fn pop_front(&mut self) -> Option<T>;
+
+    fn pop_back(&mut self) -> Option<T>;
+
+    fn is_empty(&self) -> bool;
+}
+
+/// Stack whose operations return a new version and leave `self` untouched.
+pub trait PersistentStack<T>: Sized {
+    fn prepend(&self, value: T) -> Self;
+
+    fn tail(&self) -> Self;
+
+    fn first(&self) -> Option<&T>;
+
+    fn is_empty(&self) -> bool {
+        self.first().is_none()
+    }
+}
+
+impl<T> Stack<T> for LinkedList<T> {
+    fn push(&mut self, value: T) {
+        LinkedList::push(self, value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        LinkedList::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        LinkedList::peek(self)
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        LinkedList::peek_mut(self)
+    }
+}
+
+impl<T, A: NodeAllocator> Queue<T> for UnsafeQueue<T, A> {
+    fn push(&mut self, value: T) {
+        UnsafeQueue::push(self, value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        UnsafeQueue::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        UnsafeQueue::peek(self)
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        UnsafeQueue::peek_mut(self)
+    }
+}
+
+impl<T, A: NodeAllocator> Stack<T> for UnsafeLinkedList<T, A> {
+    fn push(&mut self, value: T) {
+        UnsafeLinkedList::push(self, value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop_first()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.first()
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        self.first_mut()
+    }
+
+    fn is_empty(&self) -> bool {
+        UnsafeLinkedList::is_empty(self)
+    }
+}
+
+impl<T, A: NodeAllocator> Queue<T> for UnsafeLinkedList<T, A> {
+    fn push(&mut self, value: T) {
+        self.push_back(value)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop_first()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.first()
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        self.first_mut()
+    }
+
+    fn is_empty(&self) -> bool {
+        UnsafeLinkedList::is_empty(self)
+    }
+}
+
+impl<T, A: NodeAllocator> Deque<T> for UnsafeLinkedList<T, A> {
+    fn push_front(&mut self, value: T) {
+        UnsafeLinkedList::push(self, value)
+    }
+
+    fn push_back(&mut self, value: T) {
+        UnsafeLinkedList::push_back(self, value)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        self.pop_first()
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        UnsafeLinkedList::pop_back(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        UnsafeLinkedList::is_empty(self)
+    }
+}
+
+impl<T> Deque<T> for safe_deque::Deque<T> {
+    fn push_front(&mut self, value: T) {
+        safe_deque::Deque::push_front(self, value)
+    }
+
+    fn push_back(&mut self, value: T) {
+        safe_deque::Deque::push_back(self, value)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        safe_deque::Deque::pop_front(self)
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        safe_deque::Deque::pop_back(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.peek_front().is_none()
+    }
+}
+
+impl<T> PersistentStack<T> for ImmutableList<T> {
+    fn prepend(&self, value: T) -> Self {
+        ImmutableList::prepend(self, value)
+    }
+
+    fn tail(&self) -> Self {
+        self.drop_last()
+    }
+
+    fn first(&self) -> Option<&T> {
+        ImmutableList::first(self)
+    }
+}
+
+impl<T> PersistentStack<T> for ArcList<T> {
+    fn prepend(&self, value: T) -> Self {
+        ArcList::prepend(self, value)
+    }
+
+    fn tail(&self) -> Self {
+        self.drop_last()
+    }
+
+    fn first(&self) -> Option<&T> {
+        ArcList::first(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Deque, PersistentStack, Queue, Stack};
+    use crate::immutable_arc_list::ArcList;
+    use crate::immutable_linked_list::ImmutableList;
+    use crate::linked_list::LinkedList;
+    use crate::safe_deque;
+    use crate::unsafe_linked_list::UnsafeLinkedList;
+    use crate::unsafe_queue::UnsafeQueue;
+
+    fn drain_stack<S: Stack<i32>>(mut stack: S) -> Vec<i32> {
+        assert!(stack.is_empty());
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        assert!(!stack.is_empty());
+        if let Some(value) = stack.peek_mut() {
+            *value *= 10;
+        }
+        assert_eq!(stack.peek(), Some(&30));
+
+        let mut out = Vec::new();
+        while let Some(value) = stack.pop() {
+            out.push(value);
+        }
+        out
+    }
+
+    fn drain_queue<Q: Queue<i32>>(mut queue: Q) -> Vec<i32> {
+        assert!(queue.is_empty());
+        queue.push(1);
+        queue.push(2);
+        queue.push(3);
+        assert!(!queue.is_empty());
+        if let Some(value) = queue.peek_mut() {
+            *value *= 10;
+        }
+        assert_eq!(queue.peek(), Some(&10));
+
+        let mut out = Vec::new();
+        while let Some(value) = queue.pop() {
+            out.push(value);
+        }
+        out
+    }
+
+    fn exercise_deque<D: Deque<i32>>(mut deque: D) {
+        assert!(deque.is_empty());
+        deque.push_front(2);
+        deque.push_front(1);
+        deque.push_back(3);
+        deque.push_back(4);
+        assert!(!deque.is_empty());
+
+        assert_eq!(deque.pop_front(), Some(1));
+        assert_eq!(deque.pop_back(), Some(4));
+        assert_eq!(deque.pop_back(), Some(3));
+        assert_eq!(deque.pop_front(), Some(2));
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.pop_back(), None);
+        assert!(deque.is_empty());
+    }
+
+    fn exercise_persistent<P: PersistentStack<i32>>(empty: P) {
+        assert!(empty.is_empty());
+        let one = empty.prepend(1);
+        let two = one.prepend(2);
+
+        assert_eq!(two.first(), Some(&2));
+        assert_eq!(two.tail().first(), Some(&1));
+        assert_eq!(one.first(), Some(&1));
+        assert!(one.tail().is_empty());
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn stack() {
+        assert_eq!(drain_stack(LinkedList::new()), [30, 2, 1]);
+        assert_eq!(drain_stack(UnsafeLinkedList::new()), [30, 2, 1]);
+    }
+
+    #[test]
+    fn queue() {
+        assert_eq!(drain_queue(UnsafeQueue::new()), [10, 2, 3]);
+        assert_eq!(drain_queue(UnsafeLinkedList::new()), [10, 2, 3]);
+    }
+
+    #[test]
+    fn deque() {
+        exercise_deque(UnsafeLinkedList::new());
+        exercise_deque(safe_deque::Deque::new());
+    }
+
+    #[test]
+    fn persistent_stack() {
+        exercise_persistent(ImmutableList::new());
+        exercise_persistent(ArcList::new());
+    }
+}
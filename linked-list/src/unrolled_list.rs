@@ -0,0 +1,1066 @@
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ptr::{self, NonNull};
+
+/**
+run tests:
+MIRIFLAGS="-Zmiri-tag-raw-pointers" cargo  +nightly-2023-06-18 miri test
+ */
+pub struct UnrolledList<T, const N: usize> {
+    first: Link<T, N>,
+    last: Link<T, N>,
+    len: usize,
+    _boo: PhantomData<Box<Node<T, N>>>,
+}
+
+pub struct Iter<'a, T, const N: usize> {
+    front: Link<T, N>,
+    front_off: usize,
+    back: Link<T, N>,
+    back_off: usize,
+    len: usize,
+    _boo: PhantomData<&'a T>,
+}
+
+pub struct IterMut<'a, T, const N: usize> {
+    front: Link<T, N>,
+    front_off: usize,
+    back: Link<T, N>,
+    back_off: usize,
+    len: usize,
+    _boo: PhantomData<&'a mut T>,
+}
+
+pub struct IntoIter<T, const N: usize> {
+    list: UnrolledList<T, N>,
+}
+
+pub struct CursorMut<'a, T, const N: usize> {
+    node: Link<T, N>,
+    off: usize,
+    index: Option<usize>,
+    list: &'a mut UnrolledList<T, N>,
+}
+
+type Link<T, const N: usize> = Option<NonNull<Node<T, N>>>;
+
+struct Node<T, const N: usize> {
+    prev: Link<T, N>,
+    next: Link<T, N>,
+    len: usize,
+    items: [MaybeUninit<T>; N],
+}
+
+unsafe impl<T: Send, const N: usize> Send for UnrolledList<T, N> {}
+unsafe impl<T: Sync, const N: usize> Sync for UnrolledList<T, N> {}
+
+impl<T, const N: usize> Node<T, N> {
+    fn alloc() -> NonNull<Self> {
+        NonNull::from(Box::leak(Box::new(Node {
+            prev: None,
+            next: None,
+            len: 0,
+            items: [const { MaybeUninit::uninit() }; N],
+        })))
+    }
+
+    fn get(&self, i: usize) -> &T {
+        debug_assert!(i < self.len);
+        unsafe { self.items[i].assume_init_ref() }
+    }
+
+    fn get_mut(&mut self, i: usize) -> &mut T {
+        debug_assert!(i < self.len);
+        unsafe { self.items[i].assume_init_mut() }
+    }
+
+    fn insert(&mut self, i: usize, value: T) {
+        debug_assert!(i <= self.len && self.len < N);
+        unsafe {
+            let items = self.items.as_mut_ptr();
+            ptr::copy(items.add(i), items.add(i + 1), self.len - i);
+        }
+        self.items[i].write(value);
+        self.len += 1;
+    }
+
+    fn remove(&mut self, i: usize) -> T {
+        debug_assert!(i < self.len);
+        unsafe {
+            let value = self.items[i].assume_init_read();
+            let items = self.items.as_mut_ptr();
+            ptr::copy(items.add(i + 1), items.add(i), self.len - i - 1);
+            self.len -= 1;
+            value
+        }
+    }
+
+    /// Moves the elements from `at` onwards to the end of `dst`.
+    fn move_tail(&mut self, at: usize, dst: &mut Self) {
+        let count = self.len - at;
+        debug_assert!(dst.len + count <= N);
+        unsafe {
+            ptr::copy_nonoverlapping(
+                self.items.as_ptr().add(at),
+                dst.items.as_mut_ptr().add(dst.len),
+                count,
+            );
+        }
+        dst.len += count;
+        self.len = at;
+    }
+}
+
+impl<T, const N: usize> Drop for Node<T, N> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                self.items.as_mut_ptr().cast::<T>(),
+                self.len,
+            ));
+        }
+    }
+}
+
+impl<T, const N: usize> UnrolledList<T, N> {
+    pub fn new() -> Self {
+        const { assert!(N >= 2, "UnrolledList nodes must hold at least two elements") };
+        UnrolledList {
+            first: None,
+            last: None,
+            len: 0,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T, N> {
+        CursorMut {
+            node: None,
+            off: 0,
+            index: None,
+            list: self,
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        unsafe {
+            let node = match self.first {
+                Some(first) if (*first.as_ptr()).len < N => first,
+                _ => {
+                    let node = Node::alloc();
+                    self.link_after(node, None);
+                    node
+                }
+            };
+            (*node.as_ptr()).insert(0, value);
+        }
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        unsafe {
+            let node = match self.last {
+                Some(last) if (*last.as_ptr()).len < N => last,
+                last => {
+                    let node = Node::alloc();
+                    self.link_after(node, last);
+                    node
+                }
+            };
+            let node = &mut *node.as_ptr();
+            node.insert(node.len, value);
+        }
+        self.len += 1;
+    }
+
+    pub fn pop_first(&mut self) -> Option<T> {
+        let first = self.first?;
+        unsafe {
+            let value = (*first.as_ptr()).remove(0);
+            if (*first.as_ptr()).len == 0 {
+                self.unlink_node(first);
+            }
+            self.len -= 1;
+            Some(value)
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let last = self.last?;
+        unsafe {
+            let node = &mut *last.as_ptr();
+            let value = node.remove(node.len - 1);
+            if node.len == 0 {
+                self.unlink_node(last);
+            }
+            self.len -= 1;
+            Some(value)
+        }
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        unsafe { self.first.map(|node| (*node.as_ptr()).get(0)) }
+    }
+
+    pub fn first_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.first.map(|node| (*node.as_ptr()).get_mut(0)) }
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        unsafe {
+            self.last.map(|node| {
+                let node = &*node.as_ptr();
+                node.get(node.len - 1)
+            })
+        }
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        unsafe {
+            self.last.map(|node| {
+                let node = &mut *node.as_ptr();
+                node.get_mut(node.len - 1)
+            })
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        let (node, off) = self.locate(index);
+        unsafe { Some((*node.as_ptr()).get(off)) }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let (node, off) = self.locate(index);
+        unsafe { Some((*node.as_ptr()).get_mut(off)) }
+    }
+
+    /// Splits the target node in half when it is full.
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(
+            index <= self.len,
+            "insertion index (is {index}) should be <= len (is {})",
+            self.len
+        );
+        if index == self.len {
+            self.push_back(value);
+        } else {
+            let (node, off) = self.locate(index);
+            unsafe {
+                self.insert_at(node, off, value);
+            }
+        }
+    }
+
+    /// Merges the node with a neighbour once it drops below half full.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        let (node, off) = self.locate(index);
+        unsafe { Some(self.remove_at(node, off).0) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        let mut cur = self.first.take();
+        self.last = None;
+        self.len = 0;
+        while let Some(node) = cur {
+            unsafe {
+                cur = (*node.as_ptr()).next;
+                drop(Box::from_raw(node.as_ptr()));
+            }
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, N> {
+        Iter {
+            front: self.first,
+            front_off: 0,
+            back: self.last,
+            back_off: self.last_off(),
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, N> {
+        IterMut {
+            front: self.first,
+            front_off: 0,
+            back: self.last,
+            back_off: self.last_off(),
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+
+    fn last_off(&self) -> usize {
+        unsafe { self.last.map_or(0, |node| (*node.as_ptr()).len - 1) }
+    }
+
+    fn locate(&self, index: usize) -> (NonNull<Node<T, N>>, usize) {
+        debug_assert!(index < self.len);
+        unsafe {
+            if index < self.len / 2 {
+                let mut node = self.first.unwrap();
+                let mut off = index;
+                while off >= (*node.as_ptr()).len {
+                    off -= (*node.as_ptr()).len;
+                    node = (*node.as_ptr()).next.unwrap();
+                }
+                (node, off)
+            } else {
+                let mut node = self.last.unwrap();
+                let mut from_back = self.len - 1 - index;
+                while from_back >= (*node.as_ptr()).len {
+                    from_back -= (*node.as_ptr()).len;
+                    node = (*node.as_ptr()).prev.unwrap();
+                }
+                (node, (*node.as_ptr()).len - 1 - from_back)
+            }
+        }
+    }
+
+    /// Links a fresh node after `after`, or at the front when `after` is `None`.
+    unsafe fn link_after(&mut self, node: NonNull<Node<T, N>>, after: Link<T, N>) {
+        let next = match after {
+            Some(after) => (*after.as_ptr()).next.replace(node),
+            None => self.first.replace(node),
+        };
+        (*node.as_ptr()).prev = after;
+        (*node.as_ptr()).next = next;
+        match next {
+            Some(next) => (*next.as_ptr()).prev = Some(node),
+            None => self.last = Some(node),
+        }
+    }
+
+    unsafe fn unlink_node(&mut self, node: NonNull<Node<T, N>>) {
+        let boxed = Box::from_raw(node.as_ptr());
+        match boxed.prev {
+            Some(prev) => (*prev.as_ptr()).next = boxed.next,
+            None => self.first = boxed.next,
+        }
+        match boxed.next {
+            Some(next) => (*next.as_ptr()).prev = boxed.prev,
+            None => self.last = boxed.prev,
+        }
+    }
+
+    /// Inserts `value` at `off` within `node` and returns where it ended up.
+    unsafe fn insert_at(
+        &mut self,
+        node: NonNull<Node<T, N>>,
+        off: usize,
+        value: T,
+    ) -> (NonNull<Node<T, N>>, usize) {
+        let (mut target, mut off) = (node, off);
+        if (*node.as_ptr()).len == N {
+            let mid = N / 2;
+            let new = Node::alloc();
+            self.link_after(new, Some(node));
+            (*node.as_ptr()).move_tail(mid, &mut *new.as_ptr());
+            if off > mid {
+                target = new;
+                off -= mid;
+            }
+        }
+        (*target.as_ptr()).insert(off, value);
+        self.len += 1;
+        (target, off)
+    }
+
+    /// Removes the element at `off` within `node`. Also returns the position of the element that
+    /// followed it, `None` if it was the last one.
+    unsafe fn remove_at(
+        &mut self,
+        node: NonNull<Node<T, N>>,
+        off: usize,
+    ) -> (T, Link<T, N>, usize) {
+        let value = (*node.as_ptr()).remove(off);
+        self.len -= 1;
+
+        let len = (*node.as_ptr()).len;
+        if len == 0 {
+            let next = (*node.as_ptr()).next;
+            self.unlink_node(node);
+            return (value, next, 0);
+        }
+
+        let (mut target, mut off) = (node, off);
+        if len < N / 2 {
+            let next = (*node.as_ptr()).next;
+            let prev = (*node.as_ptr()).prev;
+            match (prev, next) {
+                (_, Some(next)) if len + (*next.as_ptr()).len <= N => {
+                    (*next.as_ptr()).move_tail(0, &mut *node.as_ptr());
+                    self.unlink_node(next);
+                }
+                (Some(prev), _) if (*prev.as_ptr()).len + len <= N => {
+                    off += (*prev.as_ptr()).len;
+                    (*node.as_ptr()).move_tail(0, &mut *prev.as_ptr());
+                    self.unlink_node(node);
+                    target = prev;
+                }
+                _ => {}
+            }
+        }
+
+        if off < (*target.as_ptr()).len {
+            (value, Some(target), off)
+        } else {
+            (value, (*target.as_ptr()).next, 0)
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for UnrolledList<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Default for UnrolledList<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for UnrolledList<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T, const N: usize> Extend<T> for UnrolledList<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for UnrolledList<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug, const N: usize> Debug for UnrolledList<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for UnrolledList<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for UnrolledList<T, N> {}
+
+impl<T: Hash, const N: usize> Hash for UnrolledList<T, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a UnrolledList<T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut UnrolledList<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, const N: usize> IntoIterator for UnrolledList<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T, const N: usize> Iterator for Iter<'a, T, N> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        unsafe {
+            let node = &*self.front?.as_ptr();
+            let item = node.get(self.front_off);
+            self.front_off += 1;
+            if self.front_off == node.len {
+                self.front = node.next;
+                self.front_off = 0;
+            }
+            Some(item)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for Iter<'_, T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        unsafe {
+            let node = &*self.back?.as_ptr();
+            let item = node.get(self.back_off);
+            if self.back_off == 0 {
+                self.back = node.prev;
+                self.back_off = node.prev.map_or(0, |prev| (*prev.as_ptr()).len - 1);
+            } else {
+                self.back_off -= 1;
+            }
+            Some(item)
+        }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for Iter<'_, T, N> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<T, const N: usize> Clone for Iter<'_, T, N> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<'a, T, const N: usize> Iterator for IterMut<'a, T, N> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        unsafe {
+            let node = self.front?.as_ptr();
+            let item = &mut *(*node).items[self.front_off].as_mut_ptr();
+            self.front_off += 1;
+            if self.front_off == (*node).len {
+                self.front = (*node).next;
+                self.front_off = 0;
+            }
+            Some(item)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IterMut<'_, T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        unsafe {
+            let node = self.back?.as_ptr();
+            let item = &mut *(*node).items[self.back_off].as_mut_ptr();
+            if self.back_off == 0 {
+                self.back = (*node).prev;
+                self.back_off = (*node).prev.map_or(0, |prev| (*prev.as_ptr()).len - 1);
+            } else {
+                self.back_off -= 1;
+            }
+            Some(item)
+        }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IterMut<'_, T, N> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_first()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> CursorMut<'_, T, N> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn move_next(&mut self) {
+        let (node, off) = self.step_next();
+        self.node = node;
+        self.off = off;
+        self.index = match (node, self.index) {
+            (None, _) => None,
+            (Some(_), Some(index)) => Some(index + 1),
+            (Some(_), None) => Some(0),
+        };
+    }
+
+    pub fn move_back(&mut self) {
+        let (node, off) = self.step_back();
+        self.node = node;
+        self.off = off;
+        self.index = match (node, self.index) {
+            (None, _) => None,
+            (Some(_), Some(index)) => Some(index - 1),
+            (Some(_), None) => Some(self.list.len - 1),
+        };
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.node.map(|node| (*node.as_ptr()).get_mut(self.off)) }
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let (node, off) = self.step_next();
+        unsafe { node.map(|node| (*node.as_ptr()).get_mut(off)) }
+    }
+
+    pub fn peek_back(&mut self) -> Option<&mut T> {
+        let (node, off) = self.step_back();
+        unsafe { node.map(|node| (*node.as_ptr()).get_mut(off)) }
+    }
+
+    pub fn insert_before(&mut self, value: T) {
+        match self.node {
+            Some(node) => unsafe {
+                let (target, off) = self.list.insert_at(node, self.off, value);
+                // The current element sits right after the new one, possibly in the next node
+                if off + 1 < (*target.as_ptr()).len {
+                    self.node = Some(target);
+                    self.off = off + 1;
+                } else {
+                    self.node = (*target.as_ptr()).next;
+                    self.off = 0;
+                }
+                *self.index.as_mut().unwrap() += 1;
+            },
+            None => self.list.push_back(value),
+        }
+    }
+
+    pub fn insert_after(&mut self, value: T) {
+        match self.node {
+            Some(node) => unsafe {
+                // Never lands at offset 0, so the current element is right before it
+                let (target, off) = self.list.insert_at(node, self.off + 1, value);
+                self.node = Some(target);
+                self.off = off - 1;
+            },
+            None => self.list.push(value),
+        }
+    }
+
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.node?;
+        let (value, next, off) = unsafe { self.list.remove_at(node, self.off) };
+        self.node = next;
+        self.off = off;
+        if next.is_none() {
+            self.index = None;
+        }
+        Some(value)
+    }
+
+    fn step_next(&self) -> (Link<T, N>, usize) {
+        unsafe {
+            match self.node {
+                Some(node) if self.off + 1 < (*node.as_ptr()).len => (Some(node), self.off + 1),
+                Some(node) => ((*node.as_ptr()).next, 0),
+                None => (self.list.first, 0),
+            }
+        }
+    }
+
+    fn step_back(&self) -> (Link<T, N>, usize) {
+        unsafe {
+            let prev = match self.node {
+                Some(node) if self.off > 0 => return (Some(node), self.off - 1),
+                Some(node) => (*node.as_ptr()).prev,
+                None => self.list.last,
+            };
+            (prev, prev.map_or(0, |prev| (*prev.as_ptr()).len - 1))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::UnrolledList;
+    use crate::rng::SplitMix64;
+    use std::rc::Rc;
+
+    fn check_links<T: PartialEq + std::fmt::Debug, const N: usize>(list: &UnrolledList<T, N>) {
+        let from_front: Vec<_> = list.iter().collect();
+        let mut from_back: Vec<_> = list.iter().rev().collect();
+        from_back.reverse();
+        assert_eq!(from_front, from_back);
+
+        let mut len = 0;
+        let mut prev = None;
+        let mut cur = list.first;
+        unsafe {
+            while let Some(node) = cur {
+                let node_len = (*node.as_ptr()).len;
+                assert!(node_len > 0 && node_len <= N);
+                assert_eq!((*node.as_ptr()).prev, prev);
+                len += node_len;
+                prev = cur;
+                cur = (*node.as_ptr()).next;
+            }
+        }
+        assert_eq!(list.last, prev);
+        assert_eq!(list.len, len);
+        assert_eq!(from_front.len(), len);
+    }
+
+    fn node_lens<T, const N: usize>(list: &UnrolledList<T, N>) -> Vec<usize> {
+        let mut lens = Vec::new();
+        let mut cur = list.first;
+        while let Some(node) = cur {
+            unsafe {
+                lens.push((*node.as_ptr()).len);
+                cur = (*node.as_ptr()).next;
+            }
+        }
+        lens
+    }
+
+    #[test]
+    fn basics() {
+        let mut list = UnrolledList::<i32, 4>::new();
+        assert_eq!(list.pop_first(), None);
+        assert_eq!(list.pop_back(), None);
+
+        list.extend(1..=6);
+        list.push(0);
+        check_links(&list);
+        assert_eq!(node_lens(&list), [1, 4, 2]);
+        assert_eq!(list.first(), Some(&0));
+        assert_eq!(list.back(), Some(&6));
+        assert_eq!(list.get(4), Some(&4));
+        assert_eq!(list.get(7), None);
+
+        *list.get_mut(3).unwrap() *= 10;
+        *list.first_mut().unwrap() = -1;
+        *list.back_mut().unwrap() = 60;
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            [-1, 1, 2, 30, 4, 5, 60]
+        );
+
+        assert_eq!(list.pop_first(), Some(-1));
+        assert_eq!(list.pop_back(), Some(60));
+        assert_eq!(list.len(), 5);
+        check_links(&list);
+    }
+
+    #[test]
+    fn split_and_merge() {
+        let mut list: UnrolledList<_, 4> = (0..8).collect();
+        assert_eq!(node_lens(&list), [4, 4]);
+
+        // Inserting into a full node splits it in half
+        list.insert(1, 100);
+        assert_eq!(node_lens(&list), [3, 2, 4]);
+        list.insert(4, 200);
+        assert_eq!(node_lens(&list), [3, 3, 4]);
+        check_links(&list);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            [0, 100, 1, 2, 200, 3, 4, 5, 6, 7]
+        );
+
+        // Dropping below half full pulls in the next node when it fits
+        assert_eq!(list.remove(1), Some(100));
+        assert_eq!(list.remove(1), Some(1));
+        assert_eq!(node_lens(&list), [4, 4]);
+        check_links(&list);
+
+        // Otherwise the node is folded into the previous one
+        assert_eq!(list.remove(7), Some(7));
+        assert_eq!(list.remove(6), Some(6));
+        assert_eq!(list.remove(5), Some(5));
+        assert_eq!(node_lens(&list), [4, 1]);
+        list.push_back(5);
+        list.pop_first();
+        assert_eq!(node_lens(&list), [3, 2]);
+        assert_eq!(list.remove(4), Some(5));
+        assert_eq!(node_lens(&list), [4]);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [2, 200, 3, 4]);
+        assert_eq!(list.remove(9), None);
+        check_links(&list);
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index (is 3) should be <= len (is 2)")]
+    fn insert_out_of_bounds() {
+        let mut list: UnrolledList<_, 4> = (0..2).collect();
+        list.insert(3, 0);
+    }
+
+    #[test]
+    fn matches_vec() {
+        let mut list = UnrolledList::<u64, 5>::new();
+        let mut model = Vec::new();
+        let mut rng = SplitMix64::new(13);
+
+        for _ in 0..3_000 {
+            let value = rng.next_u64() % 1000;
+            match rng.next_u64() % 6 {
+                0 => {
+                    list.push(value);
+                    model.insert(0, value);
+                }
+                1 => {
+                    list.push_back(value);
+                    model.push(value);
+                }
+                2 => {
+                    let index = (rng.next_u64() as usize) % (model.len() + 1);
+                    list.insert(index, value);
+                    model.insert(index, value);
+                }
+                3 if !model.is_empty() => {
+                    let index = (rng.next_u64() as usize) % model.len();
+                    assert_eq!(list.remove(index), Some(model.remove(index)));
+                }
+                4 => assert_eq!(
+                    list.pop_first(),
+                    (!model.is_empty()).then(|| model.remove(0))
+                ),
+                _ => assert_eq!(list.pop_back(), model.pop()),
+            }
+            assert_eq!(list.len(), model.len());
+        }
+        check_links(&list);
+        assert!(list.iter().eq(&model));
+        assert!(list.iter().rev().eq(model.iter().rev()));
+        assert!((0..model.len()).all(|i| list.get(i) == model.get(i)));
+    }
+
+    #[test]
+    fn double_ended_iterators() {
+        let mut list: UnrolledList<_, 3> = (0..10).collect();
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 10);
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next_back(), Some(&9));
+        assert_eq!(iter.next_back(), Some(&8));
+        assert_eq!(iter.clone().count(), 7);
+        assert_eq!(iter.collect::<Vec<_>>(), [&1, &2, &3, &4, &5, &6, &7]);
+
+        for value in list.iter_mut().rev().step_by(2) {
+            *value = -*value;
+        }
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            [0, -1, 2, -3, 4, -5, 6, -7, 8, -9]
+        );
+
+        let mut into_iter = list.into_iter();
+        assert_eq!(into_iter.next_back(), Some(-9));
+        assert_eq!(into_iter.next(), Some(0));
+        assert_eq!(into_iter.len(), 8);
+    }
+
+    #[test]
+    fn cursor_mut() {
+        let mut list: UnrolledList<_, 4> = (0..4).collect();
+        let mut cursor = list.cursor_mut();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 0));
+        assert_eq!(cursor.peek_back(), Some(&mut 3));
+
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 1));
+
+        // The node is full, so the first insert splits it
+        cursor.insert_before(10);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 1));
+        cursor.insert_after(11);
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.peek_next(), Some(&mut 11));
+        assert_eq!(cursor.peek_back(), Some(&mut 10));
+
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(4));
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        assert_eq!(cursor.remove_current(), Some(3));
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.index(), None);
+
+        // At the ghost, inserting before appends and inserting after prepends
+        cursor.insert_before(20);
+        cursor.insert_after(-1);
+        cursor.move_back();
+        assert_eq!(cursor.current(), Some(&mut 20));
+        assert_eq!(cursor.index(), Some(5));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut -1));
+        assert_eq!(cursor.index(), Some(0));
+
+        check_links(&list);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            [-1, 0, 10, 1, 11, 20]
+        );
+    }
+
+    #[test]
+    fn cursor_matches_vec() {
+        let mut list = UnrolledList::<u64, 4>::new();
+        let mut model: Vec<u64> = Vec::new();
+        let mut rng = SplitMix64::new(5);
+        // `None` is the ghost position, like the cursor's index
+        let mut at: Option<usize> = None;
+
+        let mut cursor = list.cursor_mut();
+        for step in 0..3_000 {
+            match rng.next_u64() % 5 {
+                0 => {
+                    cursor.move_next();
+                    at = match at {
+                        Some(i) if i + 1 < model.len() => Some(i + 1),
+                        Some(_) => None,
+                        None => (!model.is_empty()).then_some(0),
+                    };
+                }
+                1 => {
+                    cursor.move_back();
+                    at = match at {
+                        Some(0) => None,
+                        Some(i) => Some(i - 1),
+                        None => model.len().checked_sub(1),
+                    };
+                }
+                2 => {
+                    cursor.insert_before(step);
+                    match at.as_mut() {
+                        Some(i) => {
+                            model.insert(*i, step);
+                            *i += 1;
+                        }
+                        None => model.push(step),
+                    }
+                }
+                3 => {
+                    cursor.insert_after(step);
+                    match at {
+                        Some(i) => model.insert(i + 1, step),
+                        None => model.insert(0, step),
+                    }
+                }
+                _ => {
+                    let removed = at.map(|i| model.remove(i));
+                    assert_eq!(cursor.remove_current(), removed);
+                    if at.is_some_and(|i| i == model.len()) {
+                        at = None;
+                    }
+                }
+            }
+            assert_eq!(cursor.index(), at);
+            assert_eq!(cursor.current().copied(), at.map(|i| model[i]));
+        }
+        check_links(&list);
+        assert!(list.iter().eq(&model));
+    }
+
+    #[test]
+    fn traits() {
+        let a: UnrolledList<_, 2> = (0..5).collect();
+        let b = a.clone();
+        let mut c: UnrolledList<_, 2> = (1..5).collect();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        c.push(0);
+        assert_eq!(a, c);
+        assert_eq!(format!("{:?}", a), "[0, 1, 2, 3, 4]");
+    }
+
+    #[test]
+    fn drops_every_value() {
+        let marker = Rc::new(());
+        {
+            let mut list = UnrolledList::<_, 8>::new();
+            for _ in 0..100 {
+                list.push_back(Rc::clone(&marker));
+            }
+            list.remove(50);
+            list.insert(10, Rc::clone(&marker));
+            let mut iter = list.into_iter();
+            iter.next();
+            iter.next_back();
+        }
+        assert_eq!(Rc::strong_count(&marker), 1);
+    }
+}
@@ -1,19 +1,26 @@
+use crate::allocator::{Global, NodeAllocator};
+use std::alloc::Layout;
 use std::cmp::Ordering;
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
 use std::ptr::NonNull;
+#[cfg(debug_assertions)]
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 use std::{fmt, mem};
 
 /**
 run tests:
 MIRIFLAGS="-Zmiri-tag-raw-pointers" cargo  +nightly-2023-06-18 miri test
  */
-
-pub struct UnsafeLinkedList<T> {
+pub struct UnsafeLinkedList<T, A: NodeAllocator = Global> {
     first: Link<T>,
     last: Link<T>,
     len: usize,
+    alloc: A,
+    #[cfg(debug_assertions)]
+    id: usize,
     _boo: PhantomData<T>,
 }
 
@@ -31,13 +38,19 @@ pub struct IterMut<'a, T> {
     _boo: PhantomData<&'a mut T>,
 }
 
-pub struct IntoIter<T> {
-    list: UnsafeLinkedList<T>,
+pub struct IntoIter<T, A: NodeAllocator = Global> {
+    list: UnsafeLinkedList<T, A>,
 }
 
-pub struct CursorMut<'a, T> {
+pub struct Cursor<'a, T, A: NodeAllocator = Global> {
     cur: Link<T>,
-    list: &'a mut UnsafeLinkedList<T>,
+    list: &'a UnsafeLinkedList<T, A>,
+    index: Option<usize>,
+}
+
+pub struct CursorMut<'a, T, A: NodeAllocator = Global> {
+    cur: Link<T>,
+    list: &'a mut UnsafeLinkedList<T, A>,
     index: Option<usize>,
 }
 
@@ -47,18 +60,52 @@ struct Node<T> {
     next: Link<T>,
     prev: Link<T>,
     value: T,
+    // Id of the list holding the node, so debug builds can check a `NodeHandle` in O(1)
+    #[cfg(debug_assertions)]
+    owner: usize,
+}
+
+#[cfg(debug_assertions)]
+fn next_list_id() -> usize {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    NEXT.fetch_add(1, AtomicOrdering::Relaxed)
 }
 
+unsafe impl<T: Send, A: NodeAllocator + Send> Send for UnsafeLinkedList<T, A> {}
+unsafe impl<T: Sync, A: NodeAllocator + Sync> Sync for UnsafeLinkedList<T, A> {}
+
 impl<T> UnsafeLinkedList<T> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T, A: NodeAllocator> UnsafeLinkedList<T, A> {
+    pub fn new_in(alloc: A) -> Self {
         Self {
             first: None,
             last: None,
             len: 0,
+            alloc,
+            #[cfg(debug_assertions)]
+            id: next_list_id(),
             _boo: PhantomData,
         }
     }
-    pub fn cursor_mut(&mut self) -> CursorMut<T> {
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    pub fn cursor(&self) -> Cursor<'_, T, A> {
+        Cursor {
+            list: self,
+            cur: None,
+            index: None,
+        }
+    }
+
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T, A> {
         CursorMut {
             list: self,
             cur: None,
@@ -68,44 +115,22 @@ impl<T> UnsafeLinkedList<T> {
 
     pub fn push(&mut self, value: T) {
         unsafe {
-            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-                next: None,
-                prev: None,
-                value,
-            })));
-            if let Some(old) = self.first {
-                (*old.as_ptr()).next = Some(new);
-                (*new.as_ptr()).prev = Some(old);
-            } else {
-                self.last = Some(new);
-            }
-            self.first = Some(new);
-            self.len += 1;
+            let new = self.alloc_node(value);
+            self.link_first(new);
         }
     }
 
     pub fn push_back(&mut self, value: T) {
         unsafe {
-            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-                next: None,
-                prev: None,
-                value,
-            })));
-            if let Some(old) = self.last {
-                (*old.as_ptr()).prev = Some(new);
-                (*new.as_ptr()).next = Some(old);
-            } else {
-                self.first = Some(new);
-            }
-            self.last = Some(new);
-            self.len += 1;
+            let new = self.alloc_node(value);
+            self.link_last(new);
         }
     }
 
     pub fn pop_first(&mut self) -> Option<T> {
         unsafe {
             self.first.map(|node| {
-                let boxed_node = Box::from_raw(node.as_ptr());
+                let boxed_node = self.free_node(node);
                 let res = boxed_node.value;
 
                 self.first = boxed_node.prev;
@@ -124,7 +149,7 @@ impl<T> UnsafeLinkedList<T> {
     pub fn pop_back(&mut self) -> Option<T> {
         unsafe {
             self.last.map(|node| {
-                let boxed_node = Box::from_raw(node.as_ptr());
+                let boxed_node = self.free_node(node);
                 let res = boxed_node.value;
 
                 self.last = boxed_node.next;
@@ -143,9 +168,243 @@ impl<T> UnsafeLinkedList<T> {
     pub fn len(&self) -> usize {
         self.len
     }
+
+    fn alloc_node(&self, value: T) -> NonNull<Node<T>> {
+        let node = self
+            .alloc
+            .allocate(Layout::new::<Node<T>>())
+            .cast::<Node<T>>();
+        unsafe {
+            node.as_ptr().write(Node {
+                next: None,
+                prev: None,
+                value,
+                #[cfg(debug_assertions)]
+                owner: self.id,
+            });
+        }
+        node
+    }
+
+    unsafe fn free_node(&self, node: NonNull<Node<T>>) -> Node<T> {
+        let node_value = node.as_ptr().read();
+        self.alloc.deallocate(node.cast(), Layout::new::<Node<T>>());
+        node_value
+    }
+
+    /// Wraps a run of nodes already detached from another list of the same allocator type.
+    unsafe fn from_detached(first: Link<T>, last: Link<T>, len: usize, alloc: A) -> Self {
+        let mut list = Self::new_in(alloc);
+        list.first = first;
+        list.last = last;
+        list.len = len;
+        list.debug_adopt(first);
+        list
+    }
+
+    /// Detaches `node` from its neighbours without freeing it.
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) {
+        let prev = (*node.as_ptr()).next.take();
+        let next = (*node.as_ptr()).prev.take();
+
+        if let Some(prev) = prev {
+            (*prev.as_ptr()).prev = next;
+        } else {
+            self.first = next;
+        }
+        if let Some(next) = next {
+            (*next.as_ptr()).next = prev;
+        } else {
+            self.last = prev;
+        }
+        self.len -= 1;
+    }
+
+    unsafe fn link_first(&mut self, node: NonNull<Node<T>>) {
+        if let Some(old) = self.first {
+            (*old.as_ptr()).next = Some(node);
+            (*node.as_ptr()).prev = Some(old);
+        } else {
+            self.last = Some(node);
+        }
+        self.first = Some(node);
+        self.len += 1;
+    }
+
+    unsafe fn link_last(&mut self, node: NonNull<Node<T>>) {
+        if let Some(old) = self.last {
+            (*old.as_ptr()).prev = Some(node);
+            (*node.as_ptr()).next = Some(old);
+        } else {
+            self.first = Some(node);
+        }
+        self.last = Some(node);
+        self.len += 1;
+    }
+
+    fn node_at(&self, index: usize) -> Link<T> {
+        if index >= self.len {
+            return None;
+        }
+        unsafe {
+            if index < self.len / 2 {
+                let mut cur = self.first;
+                for _ in 0..index {
+                    cur = (*cur?.as_ptr()).prev;
+                }
+                cur
+            } else {
+                let mut cur = self.last;
+                for _ in index + 1..self.len {
+                    cur = (*cur?.as_ptr()).next;
+                }
+                cur
+            }
+        }
+    }
 }
 
-impl<T> UnsafeLinkedList<T> {
+/// Opaque pointer to a node, returned by `push_handle` and `push_back_handle`. It gives O(1)
+/// access to its node for as long as that node stays in the list it was pushed onto.
+pub struct NodeHandle<T> {
+    node: NonNull<Node<T>>,
+}
+
+// A handle only reaches its value through the list it belongs to
+unsafe impl<T: Send> Send for NodeHandle<T> {}
+unsafe impl<T: Sync> Sync for NodeHandle<T> {}
+
+impl<T> NodeHandle<T> {
+    /// # Safety
+    ///
+    /// The node must still be in `list`: not popped, removed, split or spliced away, and `list`
+    /// not dropped. Debug builds check in O(1) that the node belongs to `list`, which catches a
+    /// wrong list but not a freed node.
+    pub unsafe fn get<'a, A: NodeAllocator>(&self, list: &'a UnsafeLinkedList<T, A>) -> &'a T {
+        list.debug_check_owns(self);
+        list.handle_get(self)
+    }
+
+    /// # Safety
+    ///
+    /// Same as [`NodeHandle::get`].
+    pub unsafe fn get_mut<'a, A: NodeAllocator>(
+        &self,
+        list: &'a mut UnsafeLinkedList<T, A>,
+    ) -> &'a mut T {
+        list.debug_check_owns(self);
+        list.handle_get_mut(self)
+    }
+
+    /// # Safety
+    ///
+    /// Same as [`NodeHandle::get`].
+    pub unsafe fn remove<A: NodeAllocator>(self, list: &mut UnsafeLinkedList<T, A>) -> T {
+        list.debug_check_owns(&self);
+        list.handle_remove(self)
+    }
+
+    /// # Safety
+    ///
+    /// Same as [`NodeHandle::get`].
+    pub unsafe fn move_to_front<A: NodeAllocator>(&self, list: &mut UnsafeLinkedList<T, A>) {
+        list.debug_check_owns(self);
+        list.handle_move_to_front(self)
+    }
+
+    /// # Safety
+    ///
+    /// Same as [`NodeHandle::get`].
+    pub unsafe fn move_to_back<A: NodeAllocator>(&self, list: &mut UnsafeLinkedList<T, A>) {
+        list.debug_check_owns(self);
+        list.handle_move_to_back(self)
+    }
+}
+
+impl<T> Debug for NodeHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeHandle").finish_non_exhaustive()
+    }
+}
+
+impl<T, A: NodeAllocator> UnsafeLinkedList<T, A> {
+    pub fn push_handle(&mut self, value: T) -> NodeHandle<T> {
+        let node = self.alloc_node(value);
+        unsafe { self.link_first(node) };
+        NodeHandle { node }
+    }
+
+    pub fn push_back_handle(&mut self, value: T) -> NodeHandle<T> {
+        let node = self.alloc_node(value);
+        unsafe { self.link_last(node) };
+        NodeHandle { node }
+    }
+
+    // The handle_* methods skip the ownership check for crate types that track their nodes
+    // themselves. Their callers uphold the contract of the matching `NodeHandle` method.
+
+    pub(crate) fn back_handle(&self) -> Option<NodeHandle<T>> {
+        self.last.map(|node| NodeHandle { node })
+    }
+
+    pub(crate) fn handle_is_back(&self, handle: &NodeHandle<T>) -> bool {
+        self.last == Some(handle.node)
+    }
+
+    pub(crate) unsafe fn handle_get(&self, handle: &NodeHandle<T>) -> &T {
+        &(*handle.node.as_ptr()).value
+    }
+
+    pub(crate) unsafe fn handle_get_mut(&mut self, handle: &NodeHandle<T>) -> &mut T {
+        &mut (*handle.node.as_ptr()).value
+    }
+
+    pub(crate) unsafe fn handle_move_to_front(&mut self, handle: &NodeHandle<T>) {
+        if self.first != Some(handle.node) {
+            self.unlink(handle.node);
+            self.link_first(handle.node);
+        }
+    }
+
+    pub(crate) unsafe fn handle_move_to_back(&mut self, handle: &NodeHandle<T>) {
+        if self.last != Some(handle.node) {
+            self.unlink(handle.node);
+            self.link_last(handle.node);
+        }
+    }
+
+    pub(crate) unsafe fn handle_remove(&mut self, handle: NodeHandle<T>) -> T {
+        self.unlink(handle.node);
+        self.free_node(handle.node).value
+    }
+
+    unsafe fn debug_check_owns(&self, handle: &NodeHandle<T>) {
+        #[cfg(debug_assertions)]
+        assert!(
+            (*handle.node.as_ptr()).owner == self.id,
+            "NodeHandle used with a list that does not contain its node"
+        );
+        #[cfg(not(debug_assertions))]
+        let _ = handle;
+    }
+
+    /// Marks the detached run of nodes starting at `first` as belonging to this list. Debug
+    /// builds pay O(moved nodes) for it whenever nodes change list.
+    unsafe fn debug_adopt(&self, first: Link<T>) {
+        #[cfg(debug_assertions)]
+        {
+            let mut cur = first;
+            while let Some(node) = cur {
+                (*node.as_ptr()).owner = self.id;
+                cur = (*node.as_ptr()).prev;
+            }
+        }
+        #[cfg(not(debug_assertions))]
+        let _ = first;
+    }
+}
+
+impl<T, A: NodeAllocator> UnsafeLinkedList<T, A> {
     pub fn first(&self) -> Option<&T> {
         unsafe { Some(&(*self.first?.as_ptr()).value) }
     }
@@ -163,7 +422,7 @@ impl<T> UnsafeLinkedList<T> {
     }
 }
 
-impl<'a, T> IntoIterator for &'a UnsafeLinkedList<T> {
+impl<'a, T, A: NodeAllocator> IntoIterator for &'a UnsafeLinkedList<T, A> {
     type Item = &'a T;
     type IntoIter = Iter<'a, T>;
 
@@ -172,16 +431,16 @@ impl<'a, T> IntoIterator for &'a UnsafeLinkedList<T> {
     }
 }
 
-impl<T> IntoIterator for UnsafeLinkedList<T> {
+impl<T, A: NodeAllocator> IntoIterator for UnsafeLinkedList<T, A> {
     type Item = T;
-    type IntoIter = IntoIter<T>;
+    type IntoIter = IntoIter<T, A>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.into_iter()
+        IntoIter { list: self }
     }
 }
 
-impl<'a, T> IntoIterator for &'a mut UnsafeLinkedList<T> {
+impl<'a, T, A: NodeAllocator> IntoIterator for &'a mut UnsafeLinkedList<T, A> {
     type Item = &'a mut T;
     type IntoIter = IterMut<'a, T>;
 
@@ -195,13 +454,11 @@ impl<'a, T> Iterator for Iter<'a, T> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.len > 0 {
-            unsafe {
-                self.first.map(|node| unsafe {
-                    self.len -= 1;
-                    self.first = (*node.as_ptr()).prev;
-                    &(*node.as_ptr()).value
-                })
-            }
+            self.first.map(|node| unsafe {
+                self.len -= 1;
+                self.first = (*node.as_ptr()).prev;
+                &(*node.as_ptr()).value
+            })
         } else {
             None
         }
@@ -212,7 +469,7 @@ impl<'a, T> Iterator for Iter<'a, T> {
     }
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<T, A: NodeAllocator> Iterator for IntoIter<T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -244,7 +501,7 @@ impl<'a, T> Iterator for IterMut<'a, T> {
     }
 }
 
-impl<T> DoubleEndedIterator for IntoIter<T> {
+impl<T, A: NodeAllocator> DoubleEndedIterator for IntoIter<T, A> {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.list.pop_back()
     }
@@ -278,8 +535,8 @@ impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
     }
 }
 
-impl<T> UnsafeLinkedList<T> {
-    pub fn iter(&self) -> Iter<T> {
+impl<T, A: NodeAllocator> UnsafeLinkedList<T, A> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             first: self.first,
             last: self.last,
@@ -288,11 +545,7 @@ impl<T> UnsafeLinkedList<T> {
         }
     }
 
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter { list: self }
-    }
-
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut {
             first: self.first,
             last: self.last,
@@ -302,7 +555,7 @@ impl<T> UnsafeLinkedList<T> {
     }
 }
 
-impl<T> ExactSizeIterator for IntoIter<T> {
+impl<T, A: NodeAllocator> ExactSizeIterator for IntoIter<T, A> {
     fn len(&self) -> usize {
         self.list.len
     }
@@ -320,7 +573,7 @@ impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
     }
 }
 
-impl<T> UnsafeLinkedList<T> {
+impl<T, A: NodeAllocator> UnsafeLinkedList<T, A> {
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
@@ -328,23 +581,409 @@ impl<T> UnsafeLinkedList<T> {
     pub fn clear(&mut self) {
         while self.pop_first().is_some() {}
     }
+
+    /// Moves every element of `other` to the back of this list in O(1), leaving `other` empty.
+    /// The moved nodes are freed through this list's allocator from then on.
+    pub fn append(&mut self, other: &mut Self) {
+        let (Some(other_first), Some(other_last)) = (other.first.take(), other.last.take()) else {
+            return;
+        };
+        unsafe { self.debug_adopt(Some(other_first)) };
+        match self.last {
+            Some(last) => unsafe {
+                (*last.as_ptr()).prev = Some(other_first);
+                (*other_first.as_ptr()).next = Some(last);
+            },
+            None => self.first = Some(other_first),
+        }
+        self.last = Some(other_last);
+        self.len += mem::take(&mut other.len);
+    }
+
+    /// Moves every element of `other` to the front of this list in O(1), leaving `other` empty.
+    /// The moved nodes are freed through this list's allocator from then on.
+    pub fn prepend_list(&mut self, other: &mut Self) {
+        mem::swap(self, other);
+        self.append(other);
+        // Swapping also traded allocators, trade them back
+        mem::swap(&mut self.alloc, &mut other.alloc);
+    }
+
+    /// Reverses the list in place by swapping the links of every node, O(n). Flipping a direction
+    /// flag instead would be O(1), but every link access, cursors and node handles included,
+    /// would then have to check it.
+    pub fn reverse(&mut self) {
+        let mut cur = self.first;
+        while let Some(node) = cur {
+            unsafe {
+                let node = &mut *node.as_ptr();
+                mem::swap(&mut node.next, &mut node.prev);
+                cur = node.next;
+            }
+        }
+        mem::swap(&mut self.first, &mut self.last);
+    }
 }
 
-impl<T> Drop for UnsafeLinkedList<T> {
+impl<T, A: NodeAllocator + Clone> UnsafeLinkedList<T, A> {
+    /// Splits the list in two at `at`, returning everything from `at` onwards. Walks from
+    /// whichever end is nearer. Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len, "Cannot split off at a nonexistent index");
+        if at == 0 {
+            let alloc = self.alloc.clone();
+            return mem::replace(self, Self::new_in(alloc));
+        }
+
+        let Some(split) = self.node_at(at) else {
+            return Self::new_in(self.alloc.clone());
+        };
+        unsafe {
+            let front_last = (*split.as_ptr()).next.take().unwrap();
+            (*front_last.as_ptr()).prev = None;
+
+            let back = UnsafeLinkedList::from_detached(
+                Some(split),
+                self.last,
+                self.len - at,
+                self.alloc.clone(),
+            );
+            self.last = Some(front_last);
+            self.len = at;
+            back
+        }
+    }
+}
+
+impl<T, A: NodeAllocator> UnsafeLinkedList<T, A> {
+    /// Keeps only the elements for which `f` returns `true`, unlinking the rest in place.
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        self.retain_mut(|elem| f(elem));
+    }
+
+    pub fn retain_mut(&mut self, mut f: impl FnMut(&mut T) -> bool) {
+        self.extract_if(|elem| !f(elem)).for_each(drop);
+    }
+
+    /// Lazily unlinks and yields every element matching `filter`, front to back. Elements the
+    /// iterator never reaches stay in the list, and the list is valid at every step, so leaking
+    /// the iterator or panicking in `filter` loses nothing.
+    pub fn extract_if<F>(&mut self, filter: F) -> ExtractIf<'_, T, F, A>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        ExtractIf {
+            cur: self.first,
+            list: self,
+            filter,
+        }
+    }
+
+    /// Removes the elements in `range` and yields them. The range is cut out of the list up
+    /// front, so the list is already valid if the iterator is leaked; the drained nodes are
+    /// leaked with it. Panics if the range is out of bounds.
+    pub fn drain(&mut self, range: impl RangeBounds<usize>) -> Drain<'_, T, A> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1).expect("range start overflows"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1).expect("range end overflows"),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        assert!(
+            start <= end,
+            "drain index starts at {start} but ends at {end}"
+        );
+        assert!(
+            end <= self.len,
+            "drain end index {end} out of range for list of length {}",
+            self.len
+        );
+
+        if start == end {
+            return Drain {
+                list: self,
+                first: None,
+                last: None,
+                len: 0,
+            };
+        }
+
+        let head = self.node_at(start).unwrap();
+        let tail = self.node_at(end - 1).unwrap();
+        unsafe {
+            let before = (*head.as_ptr()).next.take();
+            let after = (*tail.as_ptr()).prev.take();
+            match before {
+                Some(before) => (*before.as_ptr()).prev = after,
+                None => self.first = after,
+            }
+            match after {
+                Some(after) => (*after.as_ptr()).next = before,
+                None => self.last = before,
+            }
+        }
+        self.len -= end - start;
+
+        Drain {
+            list: self,
+            first: Some(head),
+            last: Some(tail),
+            len: end - start,
+        }
+    }
+}
+
+pub struct ExtractIf<'a, T, F, A: NodeAllocator = Global> {
+    list: &'a mut UnsafeLinkedList<T, A>,
+    cur: Link<T>,
+    filter: F,
+}
+
+impl<T, F, A> Iterator for ExtractIf<'_, T, F, A>
+where
+    F: FnMut(&mut T) -> bool,
+    A: NodeAllocator,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            while let Some(node) = self.cur {
+                // Step past the node first so a panicking filter leaves nothing half done
+                self.cur = (*node.as_ptr()).prev;
+                if (self.filter)(&mut (*node.as_ptr()).value) {
+                    self.list.unlink(node);
+                    return Some(self.list.free_node(node).value);
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.list.len))
+    }
+}
+
+/// Owns a chain already detached from `list`; the list is only borrowed to free the nodes.
+pub struct Drain<'a, T, A: NodeAllocator = Global> {
+    list: &'a mut UnsafeLinkedList<T, A>,
+    first: Link<T>,
+    last: Link<T>,
+    len: usize,
+}
+
+impl<T, A: NodeAllocator> Iterator for Drain<'_, T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.first.map(|node| unsafe {
+            self.len -= 1;
+            self.first = (*node.as_ptr()).prev;
+            self.list.free_node(node).value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, A: NodeAllocator> DoubleEndedIterator for Drain<'_, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.last.map(|node| unsafe {
+            self.len -= 1;
+            self.last = (*node.as_ptr()).next;
+            self.list.free_node(node).value
+        })
+    }
+}
+
+impl<T, A: NodeAllocator> ExactSizeIterator for Drain<'_, T, A> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<T, A: NodeAllocator> Drop for Drain<'_, T, A> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+impl<T, A: NodeAllocator> UnsafeLinkedList<T, A> {
+    /// Stable merge sort that relinks the existing nodes, O(n log n) and without allocating.
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn sort_by_key<K: Ord>(&mut self, mut f: impl FnMut(&T) -> K) {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// If `compare` panics the list keeps all of its elements, in an unspecified order.
+    pub fn sort_by(&mut self, compare: impl FnMut(&T, &T) -> Ordering) {
+        if self.len < 2 {
+            return;
+        }
+        let rest = self.first.take();
+        MergeSort {
+            list: self,
+            runs: [None; usize::BITS as usize],
+            rest,
+            left: None,
+            right: None,
+            merged: None,
+        }
+        .run(compare);
+    }
+
+    pub fn is_sorted(&self) -> bool
+    where
+        T: PartialOrd,
+    {
+        self.iter().zip(self.iter().skip(1)).all(|(a, b)| a <= b)
+    }
+}
+
+/// Bottom-up merge sort over the `prev` chain, which runs from `first` to `last`. Every node is
+/// always reachable from one of the fields, so dropping the sorter, even while unwinding from a
+/// panicking comparison, stitches the list back together.
+struct MergeSort<'a, T, A: NodeAllocator> {
+    list: &'a mut UnsafeLinkedList<T, A>,
+    // `runs[i]` is empty or holds 2^i sorted nodes that came before every lower run
+    runs: [Link<T>; usize::BITS as usize],
+    rest: Link<T>,
+    left: Link<T>,
+    right: Link<T>,
+    merged: Link<T>,
+}
+
+impl<T, A: NodeAllocator> MergeSort<'_, T, A> {
+    fn run(mut self, mut compare: impl FnMut(&T, &T) -> Ordering) {
+        while let Some(node) = self.rest {
+            unsafe {
+                self.rest = (*node.as_ptr()).prev.take();
+            }
+            let mut carry = Some(node);
+            for level in 0.. {
+                match self.runs[level].take() {
+                    Some(run) => {
+                        self.left = Some(run);
+                        self.right = carry;
+                        carry = self.merge(&mut compare);
+                    }
+                    None => {
+                        self.runs[level] = carry;
+                        break;
+                    }
+                }
+            }
+        }
+
+        for level in 0..self.runs.len() {
+            if let Some(run) = self.runs[level].take() {
+                self.left = Some(run);
+                self.right = self.merged.take();
+                self.merged = self.merge(&mut compare);
+            }
+        }
+    }
+
+    /// Merges `left` into `right`, taking from `left` on ties, and returns the result.
+    fn merge(&mut self, compare: &mut impl FnMut(&T, &T) -> Ordering) -> Link<T> {
+        let mut tail: Link<T> = None;
+        unsafe {
+            while let (Some(left), Some(right)) = (self.left, self.right) {
+                let from = if compare(&(*right.as_ptr()).value, &(*left.as_ptr()).value)
+                    == Ordering::Less
+                {
+                    &mut self.right
+                } else {
+                    &mut self.left
+                };
+                let node = from.unwrap();
+                *from = (*node.as_ptr()).prev.take();
+
+                match tail {
+                    Some(tail) => (*tail.as_ptr()).prev = Some(node),
+                    None => self.merged = Some(node),
+                }
+                tail = Some(node);
+            }
+
+            let rest = self.left.take().or(self.right.take());
+            match tail {
+                Some(tail) => (*tail.as_ptr()).prev = rest,
+                None => self.merged = rest,
+            }
+        }
+        self.merged.take()
+    }
+}
+
+impl<T, A: NodeAllocator> Drop for MergeSort<'_, T, A> {
+    fn drop(&mut self) {
+        let chains = self.runs.iter_mut().rev().chain([
+            &mut self.merged,
+            &mut self.left,
+            &mut self.right,
+            &mut self.rest,
+        ]);
+
+        // Concatenate whatever chains are left and rebuild the `next` links on the way
+        let mut first = None;
+        let mut last: Link<T> = None;
+        unsafe {
+            for chain in chains {
+                let mut cur = chain.take();
+                while let Some(node) = cur {
+                    (*node.as_ptr()).next = last;
+                    match last {
+                        Some(last) => (*last.as_ptr()).prev = Some(node),
+                        None => first = Some(node),
+                    }
+                    last = Some(node);
+                    cur = (*node.as_ptr()).prev;
+                }
+            }
+            if let Some(last) = last {
+                (*last.as_ptr()).prev = None;
+            }
+        }
+        self.list.first = first;
+        self.list.last = last;
+    }
+}
+
+impl<T, A: NodeAllocator> Drop for UnsafeLinkedList<T, A> {
     fn drop(&mut self) {
         self.clear()
     }
 }
 
-impl<T> Default for UnsafeLinkedList<T> {
+impl<T, A: NodeAllocator + Default> Default for UnsafeLinkedList<T, A> {
     fn default() -> Self {
-        Self::new()
+        Self::new_in(A::default())
     }
 }
 
-impl<T: Clone> Clone for UnsafeLinkedList<T> {
+impl<T: Clone, A: NodeAllocator + Clone> Clone for UnsafeLinkedList<T, A> {
     fn clone(&self) -> Self {
-        let mut new_list = Self::new();
+        let mut new_list = Self::new_in(self.alloc.clone());
         for elem in self {
             new_list.push_back(elem.clone());
         }
@@ -352,7 +991,7 @@ impl<T: Clone> Clone for UnsafeLinkedList<T> {
     }
 }
 
-impl<T> Extend<T> for UnsafeLinkedList<T> {
+impl<T, A: NodeAllocator> Extend<T> for UnsafeLinkedList<T, A> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         for item in iter {
             self.push_back(item);
@@ -360,45 +999,41 @@ impl<T> Extend<T> for UnsafeLinkedList<T> {
     }
 }
 
-impl<T> FromIterator<T> for UnsafeLinkedList<T> {
+impl<T, A: NodeAllocator + Default> FromIterator<T> for UnsafeLinkedList<T, A> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let mut list = Self::new();
+        let mut list = Self::default();
         list.extend(iter);
         list
     }
 }
 
-impl<T: Debug> Debug for UnsafeLinkedList<T> {
+impl<T: Debug, A: NodeAllocator> Debug for UnsafeLinkedList<T, A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_list().entries(self).finish()
     }
 }
 
-impl<T: PartialEq> PartialEq for UnsafeLinkedList<T> {
+impl<T: PartialEq, A: NodeAllocator> PartialEq for UnsafeLinkedList<T, A> {
     fn eq(&self, other: &Self) -> bool {
         self.len() == other.len() && self.iter().eq(other)
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        self.len() != other.len() || self.iter().ne(other)
-    }
 }
 
-impl<T: Eq> Eq for UnsafeLinkedList<T> {}
+impl<T: Eq, A: NodeAllocator> Eq for UnsafeLinkedList<T, A> {}
 
-impl<T: PartialOrd> PartialOrd for UnsafeLinkedList<T> {
+impl<T: PartialOrd, A: NodeAllocator> PartialOrd for UnsafeLinkedList<T, A> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         self.iter().partial_cmp(other)
     }
 }
 
-impl<T: Ord> Ord for UnsafeLinkedList<T> {
+impl<T: Ord, A: NodeAllocator> Ord for UnsafeLinkedList<T, A> {
     fn cmp(&self, other: &Self) -> Ordering {
         self.iter().cmp(other)
     }
 }
 
-impl<T: Hash> Hash for UnsafeLinkedList<T> {
+impl<T: Hash, A: NodeAllocator> Hash for UnsafeLinkedList<T, A> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.len().hash(state);
         for item in self {
@@ -407,11 +1042,16 @@ impl<T: Hash> Hash for UnsafeLinkedList<T> {
     }
 }
 
-impl<'a, T> CursorMut<'a, T> {
+impl<'a, T, A: NodeAllocator> Cursor<'a, T, A> {
     pub fn index(&self) -> Option<usize> {
         self.index
     }
 
+    pub fn seek(&mut self, index: usize) {
+        self.cur = self.list.node_at(index);
+        self.index = self.cur.map(|_| index);
+    }
+
     pub fn move_next(&mut self) {
         if let Some(cur) = self.cur {
             unsafe {
@@ -444,11 +1084,11 @@ impl<'a, T> CursorMut<'a, T> {
         }
     }
 
-    pub fn current(&mut self) -> Option<&mut T> {
-        unsafe { self.cur.map(|node| &mut (*node.as_ptr()).value) }
+    pub fn current(&self) -> Option<&'a T> {
+        unsafe { self.cur.map(|node| &(*node.as_ptr()).value) }
     }
 
-    pub fn peek_next(&mut self) -> Option<&mut T> {
+    pub fn peek_next(&self) -> Option<&'a T> {
         unsafe {
             let next = if let Some(cur) = self.cur {
                 (*cur.as_ptr()).prev
@@ -456,11 +1096,11 @@ impl<'a, T> CursorMut<'a, T> {
                 self.list.first
             };
 
-            next.map(|node| &mut (*node.as_ptr()).value)
+            next.map(|node| &(*node.as_ptr()).value)
         }
     }
 
-    pub fn peek_back(&mut self) -> Option<&mut T> {
+    pub fn peek_back(&self) -> Option<&'a T> {
         unsafe {
             let next = if let Some(cur) = self.cur {
                 (*cur.as_ptr()).next
@@ -468,67 +1108,100 @@ impl<'a, T> CursorMut<'a, T> {
                 self.list.last
             };
 
-            next.map(|node| &mut (*node.as_ptr()).value)
+            next.map(|node| &(*node.as_ptr()).value)
         }
     }
+}
 
-    pub fn split_before(&mut self) -> UnsafeLinkedList<T> {
-        if let Some(cur) = self.cur {
-            unsafe {
-                let old_len = self.list.len;
-                let old_idx = self.index.unwrap();
-                let prev = (*cur.as_ptr()).next.take();
-                let new_len = old_len - old_idx;
+impl<T, A: NodeAllocator> Clone for Cursor<'_, T, A> {
+    fn clone(&self) -> Self {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+            index: self.index,
+        }
+    }
+}
 
-                if let Some(prev) = prev {
-                    (*prev.as_ptr()).prev = None;
-                }
+impl<T, A: NodeAllocator> CursorMut<'_, T, A> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
 
-                self.index = Some(0);
-                self.list.len = new_len;
-                self.list.first = self.cur;
+    pub fn as_cursor(&self) -> Cursor<'_, T, A> {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+            index: self.index,
+        }
+    }
+
+    pub fn seek(&mut self, index: usize) {
+        self.cur = self.list.node_at(index);
+        self.index = self.cur.map(|_| index);
+    }
 
-                UnsafeLinkedList {
-                    first: self.list.first,
-                    last: prev,
-                    len: old_len - new_len,
-                    _boo: PhantomData,
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.cur = (*cur.as_ptr()).prev;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() += 1;
+                } else {
+                    self.index = None;
                 }
             }
-        } else {
-            mem::replace(self.list, UnsafeLinkedList::new())
+        } else if !self.list.is_empty() {
+            self.cur = self.list.first;
+            self.index = Some(0);
         }
     }
 
-    pub fn split_after(&mut self) -> UnsafeLinkedList<T> {
+    pub fn move_back(&mut self) {
         if let Some(cur) = self.cur {
             unsafe {
-                let old_len = self.list.len;
-                let old_idx = self.index.unwrap();
-                let prev = (*cur.as_ptr()).prev.take();
-                let new_len = old_len - old_idx;
-
-                if let Some(prev) = prev {
-                    (*prev.as_ptr()).next = None;
+                self.cur = (*cur.as_ptr()).next;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() -= 1;
+                } else {
+                    self.index = None;
                 }
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.last;
+            self.index = Some(self.list.len - 1);
+        }
+    }
 
-                self.index = Some(0);
-                self.list.len = new_len;
-                self.list.last = self.cur;
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.cur.map(|node| &mut (*node.as_ptr()).value) }
+    }
 
-                UnsafeLinkedList {
-                    first: self.list.last,
-                    last: prev,
-                    len: old_len - new_len,
-                    _boo: PhantomData,
-                }
-            }
-        } else {
-            mem::replace(self.list, UnsafeLinkedList::new())
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        unsafe {
+            let next = if let Some(cur) = self.cur {
+                (*cur.as_ptr()).prev
+            } else {
+                self.list.first
+            };
+
+            next.map(|node| &mut (*node.as_ptr()).value)
         }
     }
 
-    pub fn splice_before(&mut self, mut input: UnsafeLinkedList<T>) {
+    pub fn peek_back(&mut self) -> Option<&mut T> {
+        unsafe {
+            let next = if let Some(cur) = self.cur {
+                (*cur.as_ptr()).next
+            } else {
+                self.list.last
+            };
+
+            next.map(|node| &mut (*node.as_ptr()).value)
+        }
+    }
+
+    pub fn splice_before(&mut self, mut input: UnsafeLinkedList<T, A>) {
         unsafe {
             if input.is_empty() {
                 return;
@@ -536,6 +1209,7 @@ impl<'a, T> CursorMut<'a, T> {
             if let Some(cur) = self.cur {
                 let in_front = input.first.take().unwrap();
                 let in_back = input.last.take().unwrap();
+                self.list.debug_adopt(Some(in_front));
 
                 if let Some(prev) = (*cur.as_ptr()).next {
                     (*prev.as_ptr()).prev = Some(in_front);
@@ -551,51 +1225,191 @@ impl<'a, T> CursorMut<'a, T> {
             } else if let Some(back) = self.list.last {
                 let in_front = input.first.take().unwrap();
                 let in_back = input.last.take().unwrap();
+                self.list.debug_adopt(Some(in_front));
+
+                (*back.as_ptr()).prev = Some(in_front);
+                (*in_front.as_ptr()).next = Some(back);
+                self.list.last = Some(in_back);
+            } else {
+                mem::swap(self.list, &mut input);
+            }
+
+            self.list.len += input.len;
+            input.len = 0;
+        }
+    }
+
+    pub fn splice_after(&mut self, mut input: UnsafeLinkedList<T, A>) {
+        unsafe {
+            if input.is_empty() {
+                return;
+            }
+            if let Some(cur) = self.cur {
+                let in_front = input.first.take().unwrap();
+                let in_back = input.last.take().unwrap();
+                self.list.debug_adopt(Some(in_front));
+
+                if let Some(next) = (*cur.as_ptr()).prev {
+                    (*next.as_ptr()).next = Some(in_back);
+                    (*in_back.as_ptr()).prev = Some(next);
+                    (*cur.as_ptr()).prev = Some(in_front);
+                    (*in_front.as_ptr()).next = Some(cur);
+                } else {
+                    (*cur.as_ptr()).prev = Some(in_front);
+                    (*in_front.as_ptr()).next = Some(cur);
+                    self.list.last = Some(in_back);
+                }
+            } else if let Some(front) = self.list.first {
+                let in_front = input.first.take().unwrap();
+                let in_back = input.last.take().unwrap();
+                self.list.debug_adopt(Some(in_front));
+
+                (*front.as_ptr()).next = Some(in_back);
+                (*in_back.as_ptr()).prev = Some(front);
+                self.list.first = Some(in_front);
+            } else {
+                mem::swap(self.list, &mut input);
+            }
+
+            self.list.len += input.len;
+            input.len = 0;
+        }
+    }
+
+    pub fn insert_before(&mut self, value: T) {
+        match self.cur {
+            Some(cur) => unsafe {
+                let new = self.list.alloc_node(value);
+                if let Some(prev) = (*cur.as_ptr()).next {
+                    (*prev.as_ptr()).prev = Some(new);
+                    (*new.as_ptr()).next = Some(prev);
+                } else {
+                    self.list.first = Some(new);
+                }
+                (*cur.as_ptr()).next = Some(new);
+                (*new.as_ptr()).prev = Some(cur);
+                self.list.len += 1;
+                *self.index.as_mut().unwrap() += 1;
+            },
+            None => self.list.push_back(value),
+        }
+    }
+
+    pub fn insert_after(&mut self, value: T) {
+        match self.cur {
+            Some(cur) => unsafe {
+                let new = self.list.alloc_node(value);
+                if let Some(next) = (*cur.as_ptr()).prev {
+                    (*next.as_ptr()).next = Some(new);
+                    (*new.as_ptr()).prev = Some(next);
+                } else {
+                    self.list.last = Some(new);
+                }
+                (*cur.as_ptr()).prev = Some(new);
+                (*new.as_ptr()).next = Some(cur);
+                self.list.len += 1;
+            },
+            None => self.list.push(value),
+        }
+    }
+
+    pub fn replace_current(&mut self, value: T) -> Result<T, T> {
+        match self.current() {
+            Some(current) => Ok(mem::replace(current, value)),
+            None => Err(value),
+        }
+    }
+
+    pub fn remove_current(&mut self) -> Option<T> {
+        let cur = self.unlink_current()?;
+        unsafe { Some(self.list.free_node(cur).value) }
+    }
+
+    fn unlink_current(&mut self) -> Link<T> {
+        let cur = self.cur?;
+        unsafe {
+            self.cur = (*cur.as_ptr()).prev;
+            self.list.unlink(cur);
+        }
+        if self.cur.is_none() {
+            self.index = None;
+        }
+        Some(cur)
+    }
+}
+
+// Detached nodes keep pointing into the list's allocator, so the new list needs its own handle
+impl<T, A: NodeAllocator + Clone> CursorMut<'_, T, A> {
+    pub fn split_before(&mut self) -> UnsafeLinkedList<T, A> {
+        if let Some(cur) = self.cur {
+            unsafe {
+                let old_len = self.list.len;
+                let old_idx = self.index.unwrap();
 
-                (*back.as_ptr()).prev = Some(in_front);
-                (*in_front.as_ptr()).next = Some(back);
-                self.list.last = Some(in_back);
-            } else {
-                mem::swap(self.list, &mut input);
-            }
+                if let Some(prev) = (*cur.as_ptr()).next.take() {
+                    (*prev.as_ptr()).prev = None;
 
-            self.list.len += input.len;
-            input.len = 0;
+                    let front = UnsafeLinkedList::from_detached(
+                        self.list.first,
+                        Some(prev),
+                        old_idx,
+                        self.list.alloc.clone(),
+                    );
+
+                    self.index = Some(0);
+                    self.list.len = old_len - old_idx;
+                    self.list.first = Some(cur);
+
+                    front
+                } else {
+                    UnsafeLinkedList::new_in(self.list.alloc.clone())
+                }
+            }
+        } else {
+            let alloc = self.list.alloc.clone();
+            mem::replace(self.list, UnsafeLinkedList::new_in(alloc))
         }
     }
 
-    pub fn splice_after(&mut self, mut input: UnsafeLinkedList<T>) {
-        unsafe {
-            if input.is_empty() {
-                return;
-            }
-            if let Some(cur) = self.cur {
-                let in_front = input.first.take().unwrap();
-                let in_back = input.last.take().unwrap();
+    pub fn split_after(&mut self) -> UnsafeLinkedList<T, A> {
+        if let Some(cur) = self.cur {
+            unsafe {
+                let old_len = self.list.len;
+                let old_idx = self.index.unwrap();
 
-                if let Some(next) = (*cur.as_ptr()).prev {
-                    (*next.as_ptr()).next = Some(in_back);
-                    (*in_back.as_ptr()).prev = Some(next);
-                    (*cur.as_ptr()).prev = Some(in_front);
-                    (*in_front.as_ptr()).next = Some(cur);
+                if let Some(next) = (*cur.as_ptr()).prev.take() {
+                    (*next.as_ptr()).next = None;
+
+                    let back = UnsafeLinkedList::from_detached(
+                        Some(next),
+                        self.list.last,
+                        old_len - old_idx - 1,
+                        self.list.alloc.clone(),
+                    );
+
+                    self.list.len = old_idx + 1;
+                    self.list.last = Some(cur);
+
+                    back
                 } else {
-                    (*cur.as_ptr()).prev = Some(in_front);
-                    (*in_front.as_ptr()).next = Some(cur);
-                    self.list.last = Some(in_back);
+                    UnsafeLinkedList::new_in(self.list.alloc.clone())
                 }
-            } else if let Some(front) = self.list.first {
-                let in_front = input.first.take().unwrap();
-                let in_back = input.last.take().unwrap();
-
-                (*front.as_ptr()).next = Some(in_back);
-                (*in_back.as_ptr()).prev = Some(front);
-                self.list.first = Some(in_front);
-            } else {
-                mem::swap(self.list, &mut input);
             }
+        } else {
+            let alloc = self.list.alloc.clone();
+            mem::replace(self.list, UnsafeLinkedList::new_in(alloc))
+        }
+    }
 
-            self.list.len += input.len;
-            input.len = 0;
+    pub fn remove_current_as_list(&mut self) -> Option<UnsafeLinkedList<T, A>> {
+        let cur = self.unlink_current()?;
+        unsafe {
+            Some(UnsafeLinkedList::from_detached(
+                Some(cur),
+                Some(cur),
+                1,
+                self.list.alloc.clone(),
+            ))
         }
     }
 }
@@ -603,6 +1417,8 @@ impl<'a, T> CursorMut<'a, T> {
 #[cfg(test)]
 mod test {
     use super::UnsafeLinkedList;
+    use std::mem;
+    use std::ops::Bound;
 
     fn generate_test() -> UnsafeLinkedList<i32> {
         list_from(&[0, 1, 2, 3, 4, 5, 6])
@@ -733,7 +1549,7 @@ mod test {
             assert_eq!(6 - i as i32, *elt);
         }
         let mut n = UnsafeLinkedList::new();
-        assert_eq!(n.iter().rev().next(), None);
+        assert_eq!(n.iter().next_back(), None);
         n.push(4);
         let mut it = n.iter().rev();
         assert_eq!(it.size_hint(), (1, Some(1)));
@@ -807,6 +1623,7 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::zero_divided_by_zero, clippy::neg_cmp_op_on_partial_ord)]
     fn test_ord_nan() {
         let nan = 0.0f64 / 0.0;
         let n = list_from(&[nan]);
@@ -843,10 +1660,8 @@ mod test {
         let list: UnsafeLinkedList<i32> = (0..10).collect();
         assert_eq!(format!("{:?}", list), "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]");
 
-        let list: UnsafeLinkedList<&str> = vec!["just", "one", "test", "more"]
-            .iter()
-            .copied()
-            .collect();
+        let list: UnsafeLinkedList<&str> =
+            ["just", "one", "test", "more"].iter().copied().collect();
         assert_eq!(format!("{:?}", list), r#"["just", "one", "test", "more"]"#);
     }
 
@@ -913,6 +1728,70 @@ mod test {
         assert_eq!(cursor.index(), Some(4));
     }
 
+    #[test]
+    fn test_cursor_move_peek_shared() {
+        let m: UnsafeLinkedList<u32> = (1..=6).collect();
+        let mut cursor = m.cursor();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&1));
+        assert_eq!(cursor.peek_back(), Some(&6));
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&1));
+        assert_eq!(cursor.peek_next(), Some(&2));
+        assert_eq!(cursor.peek_back(), None);
+        assert_eq!(cursor.index(), Some(0));
+
+        let saved = cursor.clone();
+        cursor.move_back();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.index(), None);
+        cursor.move_back();
+        assert_eq!(cursor.current(), Some(&6));
+        assert_eq!(cursor.index(), Some(5));
+        assert_eq!(saved.current(), Some(&1));
+
+        // Two shared cursors over the same list at once
+        let mut other = m.cursor();
+        other.move_next();
+        other.move_next();
+        assert_eq!(other.current(), Some(&2));
+        assert_eq!(cursor.current(), Some(&6));
+    }
+
+    #[test]
+    fn test_cursor_seek() {
+        let mut m: UnsafeLinkedList<u32> = (0..7).collect();
+        let mut cursor = m.cursor();
+        for i in 0..7 {
+            cursor.seek(i);
+            assert_eq!(cursor.index(), Some(i));
+            assert_eq!(cursor.current(), Some(&(i as u32)));
+        }
+        cursor.seek(7);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+
+        let mut cursor = m.cursor_mut();
+        cursor.seek(5);
+        assert_eq!(cursor.current(), Some(&mut 5));
+        cursor.move_back();
+        assert_eq!(cursor.index(), Some(4));
+        cursor.seek(1);
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(cursor.index(), Some(1));
+
+        let shared = cursor.as_cursor();
+        assert_eq!(shared.index(), Some(1));
+        assert_eq!(shared.current(), Some(&2));
+        assert_eq!(shared.peek_back(), Some(&0));
+
+        let empty: UnsafeLinkedList<u32> = UnsafeLinkedList::new();
+        let mut cursor = empty.cursor();
+        cursor.seek(0);
+        assert_eq!(cursor.index(), None);
+    }
+
     #[test]
     fn test_cursor_mut_insert() {
         let mut m: UnsafeLinkedList<u32> = UnsafeLinkedList::new();
@@ -937,23 +1816,24 @@ mod test {
             &[10, 7, 1, 8, 2, 3, 4, 5, 6, 9]
         );
 
-        /* remove_current not impl'd
         let mut cursor = m.cursor_mut();
         cursor.move_next();
-        cursor.move_prev();
+        cursor.move_back();
         assert_eq!(cursor.remove_current(), None);
         cursor.move_next();
         cursor.move_next();
         assert_eq!(cursor.remove_current(), Some(7));
-        cursor.move_prev();
-        cursor.move_prev();
-        cursor.move_prev();
+        cursor.move_back();
+        cursor.move_back();
+        cursor.move_back();
         assert_eq!(cursor.remove_current(), Some(9));
         cursor.move_next();
         assert_eq!(cursor.remove_current(), Some(10));
         check_links(&m);
-        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 8, 2, 3, 4, 5, 6]);
-        */
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[1, 8, 2, 3, 4, 5, 6]
+        );
 
         let mut a: UnsafeLinkedList<u32> = UnsafeLinkedList::new();
         a.extend([1, 8, 2, 3, 4, 5, 6]);
@@ -965,7 +1845,7 @@ mod test {
         q.extend([200, 201, 202, 203]);
         cursor.splice_after(p);
         cursor.splice_before(q);
-        check_links(&m);
+        check_links(&a);
         assert_eq!(
             a.iter().cloned().collect::<Vec<_>>(),
             &[200, 201, 202, 203, 1, 100, 101, 102, 103, 8, 2, 3, 4, 5, 6]
@@ -974,8 +1854,8 @@ mod test {
         cursor.move_next();
         cursor.move_back();
         let tmp = cursor.split_before();
-        assert_eq!(a.clon.into_iter().collect::<Vec<_>>(), &[]);
-        m = tmp;
+        assert_eq!(a.into_iter().collect::<Vec<_>>(), &[]);
+        a = tmp;
         let mut cursor = a.cursor_mut();
         cursor.move_next();
         cursor.move_next();
@@ -989,18 +1869,505 @@ mod test {
             tmp.into_iter().collect::<Vec<_>>(),
             &[102, 103, 8, 2, 3, 4, 5, 6]
         );
-        check_links(&m);
+        check_links(&a);
         assert_eq!(
             a.iter().cloned().collect::<Vec<_>>(),
             &[200, 201, 202, 203, 1, 100, 101]
         );
     }
 
+    #[test]
+    fn test_cursor_split() {
+        for at in 0..5 {
+            let mut m: UnsafeLinkedList<u32> = (0..5).collect();
+            let mut cursor = m.cursor_mut();
+            for _ in 0..=at {
+                cursor.move_next();
+            }
+            let front = cursor.split_before();
+            assert_eq!(cursor.index(), Some(0));
+            assert_eq!(front.len(), at);
+            assert_eq!(m.len(), 5 - at);
+            check_links(&front);
+            check_links(&m);
+            assert_eq!(
+                front.into_iter().collect::<Vec<_>>(),
+                (0..at as u32).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                m.iter().cloned().collect::<Vec<_>>(),
+                (at as u32..5).collect::<Vec<_>>()
+            );
+
+            let mut m: UnsafeLinkedList<u32> = (0..5).collect();
+            let mut cursor = m.cursor_mut();
+            for _ in 0..=at {
+                cursor.move_next();
+            }
+            let back = cursor.split_after();
+            assert_eq!(cursor.index(), Some(at));
+            assert_eq!(back.len(), 4 - at);
+            assert_eq!(m.len(), at + 1);
+            check_links(&back);
+            check_links(&m);
+            assert_eq!(
+                back.into_iter().collect::<Vec<_>>(),
+                (at as u32 + 1..5).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                m.iter().cloned().collect::<Vec<_>>(),
+                (0..=at as u32).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_cursor_mut_edit() {
+        let mut m: UnsafeLinkedList<u32> = (1..=3).collect();
+        let mut cursor = m.cursor_mut();
+
+        // At the ghost, insert_before appends and insert_after prepends
+        cursor.insert_before(4);
+        cursor.insert_after(0);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.replace_current(9), Err(9));
+
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.index(), Some(1));
+        cursor.insert_before(10);
+        assert_eq!(cursor.index(), Some(2));
+        cursor.insert_after(11);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.replace_current(12), Ok(1));
+        assert_eq!(cursor.current(), Some(&mut 12));
+        check_links(&m);
+        assert_eq!(m.len(), 7);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[0, 10, 12, 11, 2, 3, 4]
+        );
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_back();
+        assert_eq!(cursor.index(), Some(6));
+        let removed = cursor.remove_current_as_list().unwrap();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        assert_eq!(removed.len(), 1);
+        check_links(&removed);
+        assert_eq!(removed.into_iter().collect::<Vec<_>>(), &[4]);
+
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(0));
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 10));
+        check_links(&m);
+        assert_eq!(m.len(), 5);
+        assert_eq!(m.first(), Some(&10));
+        assert_eq!(m.back(), Some(&3));
+    }
+
+    #[test]
+    fn test_cursor_mut_remove_while_walking() {
+        let mut m: UnsafeLinkedList<u32> = (0..10).collect();
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        while let Some(value) = cursor.current() {
+            if *value % 3 == 0 {
+                cursor.remove_current();
+            } else {
+                cursor.move_next();
+            }
+        }
+        check_links(&m);
+        assert_eq!(m.len(), 6);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 4, 5, 7, 8]);
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        while cursor.remove_current().is_some() {}
+        assert!(m.is_empty());
+        assert_eq!(m.first(), None);
+        assert_eq!(m.back(), None);
+    }
+
+    #[test]
+    fn test_node_handles() {
+        let mut m = UnsafeLinkedList::new();
+        let two = m.push_handle(2);
+        let three = m.push_back_handle(3);
+        let one = m.push_handle(1);
+        m.push_back(4);
+        check_links(&m);
+
+        unsafe {
+            assert_eq!(*two.get(&m), 2);
+            *three.get_mut(&mut m) *= 10;
+
+            three.move_to_front(&mut m);
+            check_links(&m);
+            assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[30, 1, 2, 4]);
+
+            one.move_to_back(&mut m);
+            two.move_to_front(&mut m);
+            check_links(&m);
+            assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[2, 30, 4, 1]);
+
+            // Moving a node that is already in place is a no-op
+            two.move_to_front(&mut m);
+            one.move_to_back(&mut m);
+            assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[2, 30, 4, 1]);
+
+            assert_eq!(three.remove(&mut m), 30);
+            check_links(&m);
+            assert_eq!(one.remove(&mut m), 1);
+            check_links(&m);
+            assert_eq!(two.remove(&mut m), 2);
+            check_links(&m);
+        }
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[4]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "does not contain its node")]
+    fn test_node_handle_wrong_list() {
+        let mut a = UnsafeLinkedList::new();
+        let b: UnsafeLinkedList<i32> = (0..3).collect();
+        let handle = a.push_handle(1);
+        unsafe {
+            handle.get(&b);
+        }
+    }
+
+    #[test]
+    fn test_node_handle_follows_moves() {
+        let mut a = UnsafeLinkedList::new();
+        let mut b = UnsafeLinkedList::new();
+        let one = a.push_back_handle(1);
+        let two = b.push_back_handle(2);
+        unsafe {
+            a.append(&mut b);
+            assert_eq!(*two.get(&a), 2);
+
+            let mut back = a.split_off(1);
+            assert_eq!(*two.get(&back), 2);
+            a.cursor_mut().splice_before(mem::take(&mut back));
+            assert_eq!(*two.get(&a), 2);
+
+            b.prepend_list(&mut a);
+            assert_eq!(*one.get(&b), 1);
+            let mut cursor = b.cursor_mut();
+            cursor.move_next();
+            let mut rest = cursor.split_after();
+            *two.get_mut(&mut rest) *= 10;
+            assert_eq!(two.remove(&mut rest), 20);
+            assert_eq!(one.remove(&mut b), 1);
+            assert!(rest.is_empty());
+        }
+        check_links(&b);
+        assert!(b.is_empty());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "does not contain its node")]
+    fn test_node_handle_after_append() {
+        let mut a: UnsafeLinkedList<i32> = (0..3).collect();
+        let mut b = UnsafeLinkedList::new();
+        let handle = b.push_handle(3);
+        a.append(&mut b);
+        unsafe {
+            handle.get(&b);
+        }
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut m: UnsafeLinkedList<i32> = UnsafeLinkedList::new();
+        m.sort();
+        assert!(m.is_sorted());
+        m.push(1);
+        m.sort();
+        check_links(&m);
+
+        let mut rng = crate::rng::SplitMix64::new(3);
+        let values: Vec<_> = (0..1000).map(|_| (rng.next_u64() % 50) as i32).collect();
+        let mut m: UnsafeLinkedList<_> = values.iter().copied().collect();
+        assert!(!m.is_sorted());
+        m.sort();
+        check_links(&m);
+        assert!(m.is_sorted());
+        let mut expected = values.clone();
+        expected.sort();
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), expected);
+
+        m.sort_by(|a, b| b.cmp(a));
+        check_links(&m);
+        expected.reverse();
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_sort_stable_and_relinks() {
+        let mut rng = crate::rng::SplitMix64::new(8);
+        let pairs: Vec<_> = (0..500).map(|i| (rng.next_u64() % 7, i)).collect();
+
+        let mut m = UnsafeLinkedList::new();
+        let handles: Vec<_> = pairs.iter().map(|&pair| m.push_back_handle(pair)).collect();
+        m.sort_by_key(|&(key, _)| key);
+        check_links(&m);
+
+        let mut expected = pairs.clone();
+        expected.sort_by_key(|&(key, _)| key);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), expected);
+
+        // The original nodes were relinked rather than reallocated
+        for (handle, pair) in handles.iter().zip(&pairs) {
+            unsafe { assert_eq!(handle.get(&m), pair) };
+        }
+    }
+
+    #[test]
+    fn test_sort_panic_keeps_elements() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut m: UnsafeLinkedList<_> = (0..100).rev().collect();
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            m.sort_by(|a, b| {
+                calls += 1;
+                assert!(calls < 150, "comparison failed");
+                a.cmp(b)
+            })
+        }));
+        assert!(result.is_err());
+
+        check_links(&m);
+        let mut values: Vec<_> = m.iter().cloned().collect();
+        values.sort();
+        assert_eq!(values, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_append_and_prepend_list() {
+        let mut m: UnsafeLinkedList<_> = (0..3).collect();
+        let mut n: UnsafeLinkedList<_> = (3..6).collect();
+
+        m.append(&mut n);
+        check_links(&m);
+        check_links(&n);
+        assert!(n.is_empty());
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            (0..6).collect::<Vec<_>>()
+        );
+
+        // Appending onto or from an empty list
+        n.append(&mut m);
+        check_links(&m);
+        check_links(&n);
+        assert!(m.is_empty());
+        m.append(&mut UnsafeLinkedList::new());
+        assert!(m.is_empty());
+
+        let mut front: UnsafeLinkedList<_> = (-3..0).collect();
+        n.prepend_list(&mut front);
+        check_links(&n);
+        check_links(&front);
+        assert!(front.is_empty());
+        assert_eq!(
+            n.iter().cloned().collect::<Vec<_>>(),
+            (-3..6).collect::<Vec<_>>()
+        );
+
+        front.prepend_list(&mut n);
+        check_links(&front);
+        assert_eq!(front.len(), 9);
+        assert_eq!(front.back(), Some(&5));
+        assert_eq!(front.pop_first(), Some(-3));
+    }
+
+    #[test]
+    fn test_reverse() {
+        let mut m: UnsafeLinkedList<i32> = UnsafeLinkedList::new();
+        m.reverse();
+        check_links(&m);
+
+        m.push_back(1);
+        m.reverse();
+        check_links(&m);
+        assert_eq!(m.first(), Some(&1));
+
+        let mut m: UnsafeLinkedList<_> = (0..6).collect();
+        m.reverse();
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), [5, 4, 3, 2, 1, 0]);
+        assert_eq!(
+            m.iter().rev().cloned().collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4, 5]
+        );
+
+        // Both ends keep working after the flip
+        m.push(6);
+        m.push_back(-1);
+        m.reverse();
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            [-1, 0, 1, 2, 3, 4, 5, 6]
+        );
+        assert_eq!(m.pop_first(), Some(-1));
+        assert_eq!(m.pop_back(), Some(6));
+        check_links(&m);
+    }
+
+    #[test]
+    fn test_split_off() {
+        for at in 0..=6 {
+            let mut m: UnsafeLinkedList<_> = (0..6).collect();
+            let n = m.split_off(at);
+            check_links(&m);
+            check_links(&n);
+            assert_eq!(
+                m.iter().cloned().collect::<Vec<_>>(),
+                (0..at).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                n.iter().cloned().collect::<Vec<_>>(),
+                (at..6).collect::<Vec<_>>()
+            );
+        }
+
+        let mut m: UnsafeLinkedList<_> = (0..6).collect();
+        let mut n = m.split_off(4);
+        m.append(&mut n);
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            (0..6).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot split off at a nonexistent index")]
+    fn test_split_off_out_of_bounds() {
+        let mut m: UnsafeLinkedList<_> = (0..3).collect();
+        m.split_off(4);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut m: UnsafeLinkedList<_> = (0..10).collect();
+        m.retain(|&x| x % 3 != 0);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), [1, 2, 4, 5, 7, 8]);
+
+        m.retain_mut(|x| {
+            *x *= 10;
+            *x > 40
+        });
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), [50, 70, 80]);
+
+        m.retain(|_| false);
+        check_links(&m);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_extract_if() {
+        let mut m: UnsafeLinkedList<_> = (0..10).collect();
+        let evens: Vec<_> = m.extract_if(|x| *x % 2 == 0).collect();
+        check_links(&m);
+        assert_eq!(evens, [0, 2, 4, 6, 8]);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), [1, 3, 5, 7, 9]);
+
+        // Stopping early leaves the rest untouched
+        assert_eq!(m.extract_if(|x| *x > 2).next(), Some(3));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), [1, 5, 7, 9]);
+    }
+
+    #[test]
+    fn test_extract_if_panic() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut m: UnsafeLinkedList<_> = (0..6).collect();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            m.retain(|&x| {
+                assert!(x != 3, "predicate failed");
+                x % 2 == 1
+            })
+        }));
+        assert!(result.is_err());
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), [1, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_drain() {
+        for (range, drained) in [
+            ((Bound::Unbounded, Bound::Unbounded), 0..6),
+            ((Bound::Included(0), Bound::Excluded(2)), 0..2),
+            ((Bound::Excluded(1), Bound::Included(4)), 2..5),
+            ((Bound::Included(4), Bound::Unbounded), 4..6),
+            ((Bound::Included(3), Bound::Excluded(3)), 3..3),
+            ((Bound::Included(0), Bound::Excluded(0)), 0..0),
+            ((Bound::Included(6), Bound::Unbounded), 6..6),
+        ] {
+            let mut m: UnsafeLinkedList<_> = (0..6).collect();
+            let removed: Vec<_> = m.drain(range).collect();
+            check_links(&m);
+            assert_eq!(removed, drained.collect::<Vec<_>>());
+            let expected: Vec<_> = (0..6).filter(|x| !removed.contains(x)).collect();
+            assert_eq!(m.iter().cloned().collect::<Vec<_>>(), expected);
+            assert_eq!(m.len(), 6 - removed.len());
+        }
+
+        let mut m: UnsafeLinkedList<_> = (0..8).collect();
+        let mut drain = m.drain(2..6);
+        assert_eq!(drain.len(), 4);
+        assert_eq!(drain.next(), Some(2));
+        assert_eq!(drain.next_back(), Some(5));
+        drop(drain);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), [0, 1, 6, 7]);
+
+        // A leaked drain has already cut its range out
+        mem::forget(m.drain(1..3));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), [0, 7]);
+    }
+
+    #[test]
+    #[should_panic(expected = "drain end index 4 out of range for list of length 3")]
+    fn test_drain_out_of_bounds() {
+        let mut m: UnsafeLinkedList<_> = (0..3).collect();
+        m.drain(1..4);
+    }
+
     fn check_links<T: Eq + std::fmt::Debug>(list: &UnsafeLinkedList<T>) {
         let from_front: Vec<_> = list.iter().collect();
         let from_back: Vec<_> = list.iter().rev().collect();
         let re_reved: Vec<_> = from_back.into_iter().rev().collect();
 
         assert_eq!(from_front, re_reved);
+
+        let mut len = 0;
+        let mut prev = None;
+        let mut cur = list.first;
+        unsafe {
+            while let Some(node) = cur {
+                assert_eq!((*node.as_ptr()).next, prev);
+                prev = cur;
+                cur = (*node.as_ptr()).prev;
+                len += 1;
+            }
+        }
+        assert_eq!(list.last, prev);
+        assert_eq!(list.len, len);
     }
 }
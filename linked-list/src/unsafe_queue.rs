@@ -1,15 +1,18 @@
-use std::ptr::null_mut;
+use crate::allocator::{Global, NodeAllocator};
+use std::alloc::Layout;
+use std::ptr::{null_mut, NonNull};
 
 /**
 run tests:
 MIRIFLAGS="-Zmiri-tag-raw-pointers" cargo  +nightly-2023-06-18 miri test
  */
-pub struct UnsafeQueue<T> {
+pub struct UnsafeQueue<T, A: NodeAllocator = Global> {
     head: Link<T>,
     tail: Link<T>,
+    alloc: A,
 }
 
-pub struct IntoIter<T>(UnsafeQueue<T>);
+pub struct IntoIter<T, A: NodeAllocator = Global>(UnsafeQueue<T, A>);
 
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
@@ -26,19 +29,40 @@ struct Node<T> {
 
 type Link<T> = *mut Node<T>;
 
+unsafe impl<T: Send, A: NodeAllocator + Send> Send for UnsafeQueue<T, A> {}
+unsafe impl<T: Sync, A: NodeAllocator + Sync> Sync for UnsafeQueue<T, A> {}
+
 impl<T> UnsafeQueue<T> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T, A: NodeAllocator> UnsafeQueue<T, A> {
+    pub fn new_in(alloc: A) -> Self {
         UnsafeQueue {
             head: null_mut(),
             tail: null_mut(),
+            alloc,
         }
     }
 
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
     pub fn push(&mut self, value: T) {
-        let new_tail = Box::into_raw(Box::new(Node {
-            value,
-            next: null_mut(),
-        }));
+        let new_tail = self
+            .alloc
+            .allocate(Layout::new::<Node<T>>())
+            .cast::<Node<T>>()
+            .as_ptr();
+        unsafe {
+            new_tail.write(Node {
+                value,
+                next: null_mut(),
+            });
+        }
 
         if !self.tail.is_null() {
             unsafe {
@@ -56,7 +80,11 @@ impl<T> UnsafeQueue<T> {
             if self.head.is_null() {
                 None
             } else {
-                let head = Box::from_raw(self.head);
+                let head = self.head.read();
+                self.alloc.deallocate(
+                    NonNull::new_unchecked(self.head).cast(),
+                    Layout::new::<Node<T>>(),
+                );
                 self.head = head.next;
 
                 if self.head.is_null() {
@@ -75,9 +103,25 @@ impl<T> UnsafeQueue<T> {
     pub fn peek_mut(&mut self) -> Option<&mut T> {
         unsafe { self.head.as_mut().map(|node| &mut node.value) }
     }
+
+    /// Reverses the queue in place by relinking its nodes, so the last pushed value pops first.
+    pub fn reverse(&mut self) {
+        let mut reversed = null_mut();
+        let mut cur = self.head;
+        while !cur.is_null() {
+            unsafe {
+                let next = (*cur).next;
+                (*cur).next = reversed;
+                reversed = cur;
+                cur = next;
+            }
+        }
+        self.tail = self.head;
+        self.head = reversed;
+    }
 }
 
-impl<T> UnsafeQueue<T> {
+impl<T, A: NodeAllocator> UnsafeQueue<T, A> {
     pub fn iter(&self) -> Iter<'_, T> {
         unsafe {
             Iter {
@@ -93,13 +137,24 @@ impl<T> UnsafeQueue<T> {
             }
         }
     }
+}
+
+impl<T, A: NodeAllocator + Default> Default for UnsafeQueue<T, A> {
+    fn default() -> Self {
+        Self::new_in(A::default())
+    }
+}
+
+impl<T, A: NodeAllocator> IntoIterator for UnsafeQueue<T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
 
-    pub fn into_iter(self) -> IntoIter<T> {
+    fn into_iter(self) -> Self::IntoIter {
         IntoIter(self)
     }
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<T, A: NodeAllocator> Iterator for IntoIter<T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -133,9 +188,9 @@ impl<'a, T> Iterator for IterMut<'a, T> {
     }
 }
 
-impl<T> Drop for UnsafeQueue<T> {
+impl<T, A: NodeAllocator> Drop for UnsafeQueue<T, A> {
     fn drop(&mut self) {
-        while let Some(_) = self.pop() {}
+        while self.pop().is_some() {}
     }
 }
 
@@ -181,6 +236,30 @@ mod test {
         assert_eq!(list.pop(), None);
     }
 
+    #[test]
+    fn reverse() {
+        let mut list = UnsafeQueue::new();
+        list.reverse();
+        assert_eq!(list.pop(), None);
+
+        list.push(1);
+        list.reverse();
+        assert_eq!(list.peek(), Some(&1));
+
+        list.push(2);
+        list.push(3);
+        list.reverse();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [3, 2, 1]);
+
+        // The tail must now be the old head
+        list.push(4);
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(2));
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.pop(), None);
+    }
+
     #[test]
     fn miri_food() {
         let mut list = UnsafeQueue::new();
@@ -196,7 +275,9 @@ mod test {
 
         assert_eq!(list.peek(), Some(&3));
         list.push(6);
-        list.peek_mut().map(|x| *x *= 10);
+        if let Some(x) = list.peek_mut() {
+            *x *= 10;
+        }
         assert_eq!(list.peek(), Some(&30));
         assert_eq!(list.pop(), Some(30));
 
@@ -212,7 +293,9 @@ mod test {
         assert_eq!(iter.next(), None);
 
         assert_eq!(list.pop(), Some(400));
-        list.peek_mut().map(|x| *x *= 10);
+        if let Some(x) = list.peek_mut() {
+            *x *= 10;
+        }
         assert_eq!(list.peek(), Some(&5000));
         list.push(7);
 